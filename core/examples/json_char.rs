@@ -1,8 +1,5 @@
 use std::collections::HashMap;
 use twill_core::prelude::*;
-use std::char::{decode_utf16, REPLACEMENT_CHARACTER};
-use std::iter::FromIterator;
-use std::str::FromStr;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum JsonValue {
@@ -14,71 +11,54 @@ pub enum JsonValue {
     Object(HashMap<String, JsonValue>),
 }
 
+type BoxedParser<'a, A> = Parser<'a, char, A, Box<dyn Fn(ParseContext<'a, char>) -> ParseResult<'a, char, A> + 'a>>;
+
+/// Erases the type of `parser`, which keeps the nested parser types small enough to type-check quickly.
+fn boxed<'a, A: 'a>(parser: impl ParserRunner<'a, char, A>) -> BoxedParser<'a, A> {
+    Parser::new(Box::new(move |parse_context| parser.run(parse_context)))
+}
+
 fn space<'a>() -> Parser<'a, char, (), impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, ()> + Sized> {
     elm_of(" \t\r\n").of_many0().discard()
 }
 
-fn number<'a>() -> Parser<'a, char, f64> {
+fn number<'a>() -> BoxedParser<'a, f64> {
     let integer = elm_digit_1_9_ref() - elm_digit_ref().of_many0() | elm_ref('0');
     let frac = elm_ref('.') + elm_digit_ref().of_many1();
     let exp = elm_of("eE") + elm_of("+-").opt() + elm_digit_ref().of_many1();
-    let number = elm_ref('-').opt() + integer + frac.opt() + exp.opt();
-    number.collect().map(String::from_iter).map_res(|s| f64::from_str(&s))
+    let grammar = elm_ref('-').opt() + integer + frac.opt() + exp.opt();
+    boxed(grammar.peek() * char::float::<f64>())
 }
 
-fn string<'a>() -> Parser<'a, char, String> {
-    let special_char = elm_ref('\\')
-        | elm_ref('/')
-        | elm_ref('"')
-        | elm_ref('b').map(|_| &'\x08')
-        | elm_ref('f').map(|_| &'\x0C')
-        | elm_ref('n').map(|_| &'\n')
-        | elm_ref('r').map(|_| &'\r')
-        | elm_ref('t').map(|_| &'\t');
-    let escape_sequence = elm_ref('\\') * special_char;
-    let char_string = (none_of_ref("\\\"") | escape_sequence)
-        .map(Clone::clone)
-        .of_many1()
-        .map(String::from_iter);
-    let utf16_char= tag("\\u")
-        * elm_pred(|c: &char| c.is_digit(16))
-        .of_count(4)
-        .map(String::from_iter)
-        .map_res(|digits| u16::from_str_radix(&digits, 16));
-    let utf16_string = utf16_char.of_many1().map(|chars| {
-        decode_utf16(chars)
-            .map(|r| r.unwrap_or(REPLACEMENT_CHARACTER))
-            .collect::<String>()
-    });
-    let string = surround(elm_ref('"'), (char_string | utf16_string).of_many0(), elm_ref('"'));
-    string.map(|strings| strings.concat())
+fn string<'a>() -> BoxedParser<'a, String> {
+    boxed(escaped_string('"', '\\', EscapeTable::json()))
 }
 
-fn array<'a>() -> Parser<'a, char, Vec<JsonValue>, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, Vec<JsonValue>> + Sized> {
-    let elems = lazy(value).of_many0_sep(space() * elm_ref(',') - space());
-    surround(elm_ref('[') - space(), elems, space() * elm_ref(']'))
+fn array<'a>() -> BoxedParser<'a, Vec<JsonValue>> {
+    let elems = boxed(lazy(value).of_many0_sep(space() * elm_ref(',') - space()));
+    boxed(surround(elm_ref('[') - space(), elems, space() * elm_ref(']')))
 }
 
-fn object<'a>() -> Parser<'a, char, HashMap<String, JsonValue>> {
+fn object<'a>() -> BoxedParser<'a, HashMap<String, JsonValue>> {
     let member = string() - space() - elm_ref(':') - space() + lazy(value);
-    let members = member.of_many0_sep(space() + elm_ref(',') + space());
-    let obj = surround(elm_ref('{') + space(), members, space() + elm_ref('}'));
-    obj.map(|members| members.into_iter().collect::<HashMap<_, _>>())
+    let members = boxed(member.of_many0_sep(space() * elm_ref(',') - space()));
+    let obj = surround(elm_ref('{') - space(), members, space() * elm_ref('}'));
+    boxed(obj.map(|members| members.into_iter().collect::<HashMap<_, _>>()))
 }
 
-fn value<'a>() -> Parser<'a, char, JsonValue> {
-    (tag("null").map(|_| JsonValue::Null)
+fn value<'a>() -> BoxedParser<'a, JsonValue> {
+    let value = tag("null").map(|_| JsonValue::Null)
         | tag("true").map(|_| JsonValue::Bool(true))
         | tag("false").map(|_| JsonValue::Bool(false))
-        | number().map(|num| JsonValue::Num(num))
-        | string().map(|text| JsonValue::Str(text))
-        | array().map(|arr| JsonValue::Array(arr))
-        | object().map(|obj| JsonValue::Object(obj)))
-        - space()
+        | number().map(JsonValue::Num)
+        | string().map(JsonValue::Str)
+        | array().map(JsonValue::Array)
+        | object().map(JsonValue::Object);
+    boxed(value - space())
 }
 
-pub fn json<'a>() -> Parser<'a, char, JsonValue> {
-    space() * value() - end()
+pub fn json<'a>() -> BoxedParser<'a, JsonValue> {
+    boxed(space() * value() - end())
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,726 @@
+use crate::parse_context::ParseContext;
+use crate::parse_error::ParseError;
+use crate::parse_result::ParseResult;
+use crate::parser::*;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+/// The core rules of RFC 5234, Appendix B.1.
+const CORE_RULES: &str = r#"
+ALPHA  = %x41-5A / %x61-7A
+BIT    = "0" / "1"
+CHAR   = %x01-7F
+CR     = %x0D
+CRLF   = CR LF
+CTL    = %x00-1F / %x7F
+DIGIT  = %x30-39
+DQUOTE = %x22
+HEXDIG = DIGIT / "A" / "B" / "C" / "D" / "E" / "F"
+HTAB   = %x09
+LF     = %x0A
+LWSP   = *(WSP / CRLF WSP)
+OCTET  = %x00-FF
+SP     = %x20
+VCHAR  = %x21-7E
+WSP    = SP / HTAB
+"#;
+
+/// An error in an ABNF grammar text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbnfError {
+  offset: usize,
+  message: String,
+}
+
+impl AbnfError {
+  fn new(offset: usize, message: impl Into<String>) -> Self {
+    Self {
+      offset,
+      message: message.into(),
+    }
+  }
+
+  /// Returns the offset of the error in the grammar text, in characters.
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+
+  /// Returns the description of the error.
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl Display for AbnfError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} at {}", self.message, self.offset)
+  }
+}
+
+impl std::error::Error for AbnfError {}
+
+#[derive(Debug, Clone)]
+enum AbnfExpr {
+  Bytes {
+    bytes: Vec<u8>,
+    case_sensitive: bool,
+  },
+  Range(u8, u8),
+  Rule(usize),
+  Concat(Vec<AbnfExpr>),
+  Alt(Vec<AbnfExpr>),
+  Repeat {
+    min: usize,
+    max: Option<usize>,
+    expr: Box<AbnfExpr>,
+  },
+}
+
+impl AbnfExpr {
+  fn is_nullable(&self, nullable: &[bool]) -> bool {
+    match self {
+      AbnfExpr::Bytes { bytes, .. } => bytes.is_empty(),
+      AbnfExpr::Range(..) => false,
+      AbnfExpr::Rule(index) => nullable[*index],
+      AbnfExpr::Concat(exprs) => exprs.iter().all(|e| e.is_nullable(nullable)),
+      AbnfExpr::Alt(exprs) => exprs.iter().any(|e| e.is_nullable(nullable)),
+      AbnfExpr::Repeat { min, expr, .. } => *min == 0 || expr.is_nullable(nullable),
+    }
+  }
+
+  /// Collects the rules that may be invoked before any input is consumed.
+  fn leftmost_rules(&self, nullable: &[bool], rules: &mut Vec<usize>) {
+    match self {
+      AbnfExpr::Bytes { .. } | AbnfExpr::Range(..) => {}
+      AbnfExpr::Rule(index) => rules.push(*index),
+      AbnfExpr::Concat(exprs) => {
+        for e in exprs {
+          e.leftmost_rules(nullable, rules);
+          if !e.is_nullable(nullable) {
+            break;
+          }
+        }
+      }
+      AbnfExpr::Alt(exprs) => exprs.iter().for_each(|e| e.leftmost_rules(nullable, rules)),
+      AbnfExpr::Repeat { max, expr, .. } => {
+        if *max != Some(0) {
+          expr.leftmost_rules(nullable, rules)
+        }
+      }
+    }
+  }
+}
+
+struct AbnfRule {
+  name: String,
+  offset: usize,
+  expr: AbnfExpr,
+}
+
+/// An ABNF (RFC 5234) grammar loaded at runtime, from which recognisers over `u8` are compiled.
+///
+/// Rule names are case-insensitive, and the core rules (`ALPHA`, `DIGIT`, `HEXDIG`, `CRLF`, `WSP`...) are
+/// predefined; a grammar may redefine them or extend them with `=/`. Quoted strings match case-insensitively, and
+/// `%s"..."` case-sensitively. Numeric values (`%x41`, `%d13.10`, `%x30-39`) must fit in a byte, and prose values
+/// `<...>` are rejected.
+///
+/// Alternatives and repetitions backtrack fully, so `*DIGIT DIGIT` matches as in ABNF, and a parser returns the longest
+/// match of its rule. References to undefined rules and left recursion are rejected when the grammar is loaded.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// let grammar = AbnfGrammar::new(
+///   r#"
+///   IPv4address = dec-octet "." dec-octet "." dec-octet "." dec-octet
+///   dec-octet   = DIGIT                 ; 0-9
+///               / %x31-39 DIGIT         ; 10-99
+///               / "1" 2DIGIT            ; 100-199
+///               / "2" %x30-34 DIGIT     ; 200-249
+///               / "25" %x30-35          ; 250-255
+///   "#,
+/// )
+/// .unwrap();
+///
+/// let parser = grammar.parser("ipv4address").unwrap() - end();
+///
+/// assert_eq!(parser.parse(b"192.168.0.255").success().unwrap(), b"192.168.0.255");
+/// assert!(parser.parse(b"192.168.0.256").is_failure());
+/// ```
+pub struct AbnfGrammar {
+  rules: Vec<AbnfRule>,
+}
+
+impl AbnfGrammar {
+  /// Load a grammar from its text.
+  pub fn new(text: &str) -> Result<Self, AbnfError> {
+    let mut reader = RuleReader::default();
+    reader.read(CORE_RULES, true)?;
+    reader.read(text, false)?;
+    reader.finish()
+  }
+
+  /// Returns the names of the rules, core rules first.
+  pub fn rule_names(&self) -> impl Iterator<Item = &str> {
+    self.rules.iter().map(|rule| rule.name.as_str())
+  }
+
+  /// Compile the rule `name` and the rules it refers to into a parser that returns the matched bytes.
+  ///
+  /// Returns `None` if the grammar has no such rule. The parser does not borrow the grammar.
+  pub fn parser<'a>(&self, name: &str) -> Option<Parser<'a, u8, &'a [u8], impl ParserFn<'a, u8, &'a [u8]>>> {
+    let start = self
+      .rules
+      .iter()
+      .position(|rule| rule.name.eq_ignore_ascii_case(name))?;
+    let rules: Rc<[AbnfExpr]> = self.rules.iter().map(|rule| rule.expr.clone()).collect();
+    Some(Parser::new(move |parse_context: ParseContext<'a, u8>| {
+      let mut matcher = Matcher::new(&rules, parse_context.original_input());
+      let offset = parse_context.next_offset();
+      match matcher.ends(&AbnfExpr::Rule(start), offset).last() {
+        Some(end) => {
+          let value = parse_context.slice_with_len(end - offset);
+          ParseResult::successful(parse_context, value, end - offset)
+        }
+        None => {
+          let pe = matcher.error();
+          ParseResult::failed_with_uncommitted(parse_context, pe)
+        }
+      }
+    }))
+  }
+}
+
+/// A match of the rules of a grammar against one input.
+///
+/// Every expression is matched as a set of end offsets, so that alternatives and repetitions backtrack fully. The ends
+/// of each rule at each offset are memoized.
+struct Matcher<'g, 'a> {
+  rules: &'g [AbnfExpr],
+  input: &'a [u8],
+  memo: HashMap<(usize, usize), Rc<[usize]>>,
+  furthest: Option<(usize, String)>,
+}
+
+impl<'g, 'a> Matcher<'g, 'a> {
+  fn new(rules: &'g [AbnfExpr], input: &'a [u8]) -> Self {
+    Self {
+      rules,
+      input,
+      memo: HashMap::new(),
+      furthest: None,
+    }
+  }
+
+  /// Returns the end offsets of the matches of `expr` at `offset`, in increasing order.
+  fn ends(&mut self, expr: &AbnfExpr, offset: usize) -> Rc<[usize]> {
+    match expr {
+      AbnfExpr::Bytes { bytes, case_sensitive } => {
+        for (index, b) in bytes.iter().enumerate() {
+          match self.input.get(offset + index) {
+            Some(actual) if actual == b || (!case_sensitive && actual.eq_ignore_ascii_case(b)) => {}
+            actual => {
+              let msg = format!(
+                "literal {:?} expect: {:?}, found: {:?}",
+                String::from_utf8_lossy(bytes),
+                *b as char,
+                actual.map(|actual| *actual as char)
+              );
+              return self.fail(offset + index, msg);
+            }
+          }
+        }
+        Rc::new([offset + bytes.len()])
+      }
+      AbnfExpr::Range(first, last) => match self.input.get(offset) {
+        Some(actual) if (first..=last).contains(&actual) => Rc::new([offset + 1]),
+        actual => {
+          let msg = format!(
+            "expect: %x{:02X}-{:02X}, found: {:?}",
+            first,
+            last,
+            actual.map(|actual| *actual as char)
+          );
+          self.fail(offset, msg)
+        }
+      },
+      AbnfExpr::Rule(index) => {
+        if let Some(ends) = self.memo.get(&(*index, offset)) {
+          return ends.clone();
+        }
+        let rules = self.rules;
+        let ends = self.ends(&rules[*index], offset);
+        self.memo.insert((*index, offset), ends.clone());
+        ends
+      }
+      AbnfExpr::Concat(exprs) => {
+        let mut frontier = BTreeSet::from([offset]);
+        for e in exprs {
+          frontier = self.ends_from(e, &frontier);
+        }
+        frontier.into_iter().collect()
+      }
+      AbnfExpr::Alt(exprs) => {
+        let mut ends = BTreeSet::new();
+        for e in exprs {
+          ends.extend(self.ends(e, offset).iter());
+        }
+        ends.into_iter().collect()
+      }
+      AbnfExpr::Repeat { min, max, expr } => {
+        let (mut ends, mut frontier, mut count) = (BTreeSet::new(), BTreeSet::from([offset]), 0);
+        loop {
+          if count >= *min {
+            ends.extend(&frontier);
+          }
+          if frontier.is_empty() || max.is_some_and(|max| count >= max) {
+            break;
+          }
+          frontier = self.ends_from(expr, &frontier);
+          count += 1;
+          // Without an upper bound, the matches following an offset already taken as an end are known
+          if max.is_none() && count >= *min {
+            frontier.retain(|end| !ends.contains(end));
+          }
+        }
+        ends.into_iter().collect()
+      }
+    }
+  }
+
+  /// Returns the end offsets of the matches of `expr` at any of `offsets`.
+  fn ends_from(&mut self, expr: &AbnfExpr, offsets: &BTreeSet<usize>) -> BTreeSet<usize> {
+    let mut ends = BTreeSet::new();
+    for offset in offsets {
+      ends.extend(self.ends(expr, *offset).iter());
+    }
+    ends
+  }
+
+  /// Records a mismatch at `offset`, keeping the one furthest into the input for the error.
+  fn fail(&mut self, offset: usize, message: String) -> Rc<[usize]> {
+    if self.furthest.as_ref().is_none_or(|(furthest, _)| offset > *furthest) {
+      self.furthest = Some((offset, message));
+    }
+    Rc::new([])
+  }
+
+  /// Returns the error of the furthest mismatch.
+  fn error(self) -> ParseError<'a, u8> {
+    match self.furthest {
+      Some((offset, message)) if offset < self.input.len() => ParseError::of_mismatch(self.input, offset, 1, message),
+      _ => ParseError::of_in_complete(),
+    }
+  }
+}
+
+/// A recursive descent reader of rule lists, accumulating the rules of several texts.
+#[derive(Default)]
+struct RuleReader {
+  chars: Vec<char>,
+  index: usize,
+  rules: Vec<AbnfRule>,
+  core: Vec<bool>,
+  references: Vec<(String, usize)>,
+}
+
+impl RuleReader {
+  fn read(&mut self, text: &str, core: bool) -> Result<(), AbnfError> {
+    self.chars = text.chars().collect();
+    self.index = 0;
+    self.skip_spacing();
+    while self.index < self.chars.len() {
+      let offset = self.index;
+      let name = self
+        .rule_name()
+        .ok_or_else(|| AbnfError::new(offset, "expected a rule name"))?;
+      let incremental = if self.symbol("=/") {
+        true
+      } else if self.symbol("=") {
+        false
+      } else {
+        return Err(AbnfError::new(self.index, "expected = or =/"));
+      };
+      let expr = self.alternation()?;
+      let position = self.rules.iter().position(|rule| rule.name.eq_ignore_ascii_case(&name));
+      match position {
+        Some(index) if incremental => {
+          let rule = &mut self.rules[index];
+          rule.expr = match std::mem::replace(&mut rule.expr, AbnfExpr::Alt(vec![])) {
+            AbnfExpr::Alt(mut alternatives) => {
+              alternatives.push(expr);
+              AbnfExpr::Alt(alternatives)
+            }
+            previous => AbnfExpr::Alt(vec![previous, expr]),
+          };
+          if self.core[index] && !core {
+            rule.offset = offset;
+          }
+          self.core[index] &= core;
+        }
+        None if incremental => return Err(AbnfError::new(offset, format!("=/ on undefined rule {}", name))),
+        Some(index) if self.core[index] && !core => {
+          self.rules[index] = AbnfRule { name, offset, expr };
+          self.core[index] = false;
+        }
+        Some(_) => return Err(AbnfError::new(offset, format!("duplicate rule {}", name))),
+        None => {
+          self.rules.push(AbnfRule { name, offset, expr });
+          self.core.push(core);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Resolves the references of all rules read so far, and rejects left recursion.
+  fn finish(mut self) -> Result<AbnfGrammar, AbnfError> {
+    for index in 0..self.rules.len() {
+      self.rules[index].expr = self.resolve(self.rules[index].expr.clone())?;
+    }
+    let mut nullable = vec![false; self.rules.len()];
+    let mut changed = true;
+    while changed {
+      changed = false;
+      for (index, rule) in self.rules.iter().enumerate() {
+        if !nullable[index] && rule.expr.is_nullable(&nullable) {
+          nullable[index] = true;
+          changed = true;
+        }
+      }
+    }
+    let leftmost = self
+      .rules
+      .iter()
+      .map(|rule| {
+        let mut rules = vec![];
+        rule.expr.leftmost_rules(&nullable, &mut rules);
+        rules
+      })
+      .collect::<Vec<_>>();
+    for (index, rule) in self.rules.iter().enumerate() {
+      let mut visited = vec![false; self.rules.len()];
+      let mut stack = leftmost[index].clone();
+      while let Some(next) = stack.pop() {
+        if next == index {
+          return Err(AbnfError::new(
+            rule.offset,
+            format!("left recursion in rule {}", rule.name),
+          ));
+        }
+        if !visited[next] {
+          visited[next] = true;
+          stack.extend(&leftmost[next]);
+        }
+      }
+    }
+    Ok(AbnfGrammar { rules: self.rules })
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.index).copied()
+  }
+
+  fn skip_spacing(&mut self) {
+    while let Some(c) = self.peek() {
+      if c.is_whitespace() {
+        self.index += 1;
+      } else if c == ';' {
+        while self.peek().is_some_and(|c| c != '\n') {
+          self.index += 1;
+        }
+      } else {
+        break;
+      }
+    }
+  }
+
+  fn symbol(&mut self, symbol: &str) -> bool {
+    let length = symbol.chars().count();
+    if self.chars[self.index..].iter().take(length).copied().eq(symbol.chars()) {
+      self.index += length;
+      self.skip_spacing();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn rule_name(&mut self) -> Option<String> {
+    let start = self.index;
+    if !self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+      return None;
+    }
+    while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '-') {
+      self.index += 1;
+    }
+    let name = String::from_iter(&self.chars[start..self.index]);
+    self.skip_spacing();
+    Some(name)
+  }
+
+  fn number(&mut self, radix: u32) -> Option<usize> {
+    let start = self.index;
+    while self.peek().is_some_and(|c| c.is_digit(radix)) {
+      self.index += 1;
+    }
+    usize::from_str_radix(&String::from_iter(&self.chars[start..self.index]), radix).ok()
+  }
+
+  fn alternation(&mut self) -> Result<AbnfExpr, AbnfError> {
+    let mut alternatives = vec![self.concatenation()?];
+    while self.symbol("/") {
+      alternatives.push(self.concatenation()?);
+    }
+    Ok(match alternatives.len() {
+      1 => alternatives.pop().unwrap(),
+      _ => AbnfExpr::Alt(alternatives),
+    })
+  }
+
+  fn concatenation(&mut self) -> Result<AbnfExpr, AbnfError> {
+    let mut exprs = vec![];
+    while let Some(expr) = self.repetition()? {
+      exprs.push(expr);
+    }
+    match exprs.len() {
+      0 => Err(AbnfError::new(self.index, "expected an element")),
+      1 => Ok(exprs.pop().unwrap()),
+      _ => Ok(AbnfExpr::Concat(exprs)),
+    }
+  }
+
+  fn repetition(&mut self) -> Result<Option<AbnfExpr>, AbnfError> {
+    let offset = self.index;
+    let min = self.number(10);
+    let (min, max) = if self.peek() == Some('*') {
+      self.index += 1;
+      (min.unwrap_or(0), self.number(10))
+    } else {
+      (min.unwrap_or(1), min.or(Some(1)))
+    };
+    if max.is_some_and(|max| max < min) {
+      return Err(AbnfError::new(offset, "repetition out of order"));
+    }
+    match self.element()? {
+      Some(expr) if (min, max) == (1, Some(1)) => Ok(Some(expr)),
+      Some(expr) => Ok(Some(AbnfExpr::Repeat {
+        min,
+        max,
+        expr: Box::new(expr),
+      })),
+      None if self.index == offset => Ok(None),
+      None => Err(AbnfError::new(self.index, "expected an element after the repetition")),
+    }
+  }
+
+  fn element(&mut self) -> Result<Option<AbnfExpr>, AbnfError> {
+    let offset = self.index;
+    match self.peek() {
+      Some(open @ ('(' | '[')) => {
+        self.symbol(&open.to_string());
+        let expr = self.alternation()?;
+        let close = if open == '(' { ")" } else { "]" };
+        if !self.symbol(close) {
+          return Err(AbnfError::new(self.index, format!("expected {}", close)));
+        }
+        Ok(Some(match open {
+          '(' => expr,
+          _ => AbnfExpr::Repeat {
+            min: 0,
+            max: Some(1),
+            expr: Box::new(expr),
+          },
+        }))
+      }
+      Some('"') => self.char_val(false).map(Some),
+      Some('%') => self.num_val().map(Some),
+      Some('<') => Err(AbnfError::new(offset, "prose values are not supported")),
+      _ => match self.rule_name() {
+        Some(_) if self.peek() == Some('=') => {
+          self.index = offset;
+          Ok(None)
+        }
+        Some(name) => {
+          self.references.push((name, offset));
+          Ok(Some(AbnfExpr::Rule(self.references.len() - 1)))
+        }
+        None => Ok(None),
+      },
+    }
+  }
+
+  fn char_val(&mut self, case_sensitive: bool) -> Result<AbnfExpr, AbnfError> {
+    let start = self.index;
+    let length = self.chars[start + 1..]
+      .iter()
+      .position(|c| *c == '"')
+      .ok_or_else(|| AbnfError::new(start, "unterminated string"))?;
+    let text = String::from_iter(&self.chars[start + 1..start + 1 + length]);
+    if !text.chars().all(|c| (' '..='~').contains(&c)) {
+      return Err(AbnfError::new(start, "invalid character in string"));
+    }
+    self.index = start + length + 2;
+    self.skip_spacing();
+    Ok(AbnfExpr::Bytes {
+      bytes: text.into_bytes(),
+      case_sensitive,
+    })
+  }
+
+  fn num_val(&mut self) -> Result<AbnfExpr, AbnfError> {
+    let start = self.index;
+    self.index += 1;
+    let radix = match self.peek().map(|c| c.to_ascii_lowercase()) {
+      Some('s' | 'i') if self.chars.get(self.index + 1) == Some(&'"') => {
+        let case_sensitive = self.peek().is_some_and(|c| c.eq_ignore_ascii_case(&'s'));
+        self.index += 1;
+        return self.char_val(case_sensitive);
+      }
+      Some('b') => 2,
+      Some('d') => 10,
+      Some('x') => 16,
+      _ => return Err(AbnfError::new(start, "expected b, d or x after %")),
+    };
+    self.index += 1;
+    let byte = |reader: &mut Self| {
+      let offset = reader.index;
+      match reader.number(radix) {
+        Some(value) if value <= 0xFF => Ok(value as u8),
+        Some(_) => Err(AbnfError::new(offset, "value out of byte range")),
+        None => Err(AbnfError::new(offset, "expected a value")),
+      }
+    };
+    let first = byte(self)?;
+    let expr = if self.peek() == Some('-') {
+      self.index += 1;
+      let last = byte(self)?;
+      if last < first {
+        return Err(AbnfError::new(start, "range out of order"));
+      }
+      AbnfExpr::Range(first, last)
+    } else {
+      let mut bytes = vec![first];
+      while self.peek() == Some('.') {
+        self.index += 1;
+        bytes.push(byte(self)?);
+      }
+      AbnfExpr::Bytes {
+        bytes,
+        case_sensitive: true,
+      }
+    };
+    self.skip_spacing();
+    Ok(expr)
+  }
+
+  /// Replaces the indices of references by the indices of the rules they name.
+  fn resolve(&self, expr: AbnfExpr) -> Result<AbnfExpr, AbnfError> {
+    Ok(match expr {
+      AbnfExpr::Rule(reference) => {
+        let (name, offset) = &self.references[reference];
+        match self.rules.iter().position(|rule| rule.name.eq_ignore_ascii_case(name)) {
+          Some(index) => AbnfExpr::Rule(index),
+          None => return Err(AbnfError::new(*offset, format!("undefined rule {}", name))),
+        }
+      }
+      AbnfExpr::Concat(exprs) => {
+        AbnfExpr::Concat(exprs.into_iter().map(|e| self.resolve(e)).collect::<Result<_, _>>()?)
+      }
+      AbnfExpr::Alt(exprs) => AbnfExpr::Alt(exprs.into_iter().map(|e| self.resolve(e)).collect::<Result<_, _>>()?),
+      AbnfExpr::Repeat { min, max, expr } => AbnfExpr::Repeat {
+        min,
+        max,
+        expr: Box::new(self.resolve(*expr)?),
+      },
+      expr => expr,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_abnf_repetition_and_incremental_alternatives() {
+    let grammar = AbnfGrammar::new(
+      "header = field-name \":\" *WSP field-value CRLF\r\n\
+       field-name = 1*tchar\r\n\
+       tchar = ALPHA / DIGIT\r\n\
+       tchar =/ \"-\" / \"_\"\r\n\
+       field-value = *ALPHA %s\"!\" 2*3HEXDIG\r\n",
+    )
+    .unwrap();
+    let parser = grammar.parser("HEADER").unwrap() - end();
+
+    assert!(parser.parse(b"X-Trace_1: \tok!fF0\r\n").is_success());
+    assert!(parser.parse(b"X-Trace: ok!F\r\n").is_failure());
+    assert!(parser.parse(b"X-Trace: ok!FFFF\r\n").is_failure());
+    assert!(parser.parse(b"X Trace: ok!FF\r\n").is_failure());
+    assert!(grammar.rule_names().any(|name| name == "HEXDIG"));
+  }
+
+  #[test]
+  fn test_abnf_grammar_errors() {
+    let cases = [
+      ("a = \"x\" b", 8, "undefined rule b"),
+      ("a =/ \"x\"", 0, "=/ on undefined rule a"),
+      ("a = \"x\"\nA = \"y\"", 8, "duplicate rule A"),
+      ("a = %x100", 6, "value out of byte range"),
+      ("a = 3*2\"x\"", 4, "repetition out of order"),
+      ("a = <prose>", 4, "prose values are not supported"),
+      ("a = a \"x\" / \"y\"", 0, "left recursion in rule a"),
+      ("a = b / \"x\"\nb = *\"y\" [c] a\nc = 0c", 0, "left recursion in rule a"),
+      ("ALPHA =/ *WSP ALPHA", 0, "left recursion in rule ALPHA"),
+    ];
+    for (text, offset, message) in cases {
+      let error = AbnfGrammar::new(text).err().unwrap();
+      assert_eq!((error.offset(), error.message()), (offset, message), "{}", text);
+    }
+  }
+
+  #[test]
+  fn test_abnf_backtracking() {
+    let grammar = AbnfGrammar::new(
+      r#"
+      IPv6address =                            6( h16 ":" ) ls32
+                  /                       "::" 5( h16 ":" ) ls32
+                  / [               h16 ] "::" 4( h16 ":" ) ls32
+                  / [ *1( h16 ":" ) h16 ] "::" 3( h16 ":" ) ls32
+                  / [ *2( h16 ":" ) h16 ] "::" 2( h16 ":" ) ls32
+                  / [ *3( h16 ":" ) h16 ] "::"    h16 ":"   ls32
+                  / [ *4( h16 ":" ) h16 ] "::"              ls32
+                  / [ *5( h16 ":" ) h16 ] "::"              h16
+                  / [ *6( h16 ":" ) h16 ] "::"
+      h16         = 1*4HEXDIG
+      ls32        = ( h16 ":" h16 ) / IPv4address
+      IPv4address = dec-octet "." dec-octet "." dec-octet "." dec-octet
+      dec-octet   = DIGIT / %x31-39 DIGIT / "1" 2DIGIT / "2" %x30-34 DIGIT / "25" %x30-35
+      digits      = *DIGIT DIGIT
+      "#,
+    )
+    .unwrap();
+    let parser = grammar.parser("IPv6address").unwrap() - end();
+
+    for address in [
+      "1::",
+      "1:2::",
+      "fe80::1:2",
+      "1:2:3::4",
+      "::ffff:192.0.2.1",
+      "1:2:3:4:5:6:7:8",
+    ] {
+      assert!(parser.parse(address.as_bytes()).is_success(), "{}", address);
+    }
+    for address in ["1:::2", "1:2:3:4:5:6:7", "12345::"] {
+      assert!(parser.parse(address.as_bytes()).is_failure(), "{}", address);
+    }
+    let result = grammar.parser("digits").unwrap().parse(b"123x");
+    assert_eq!(result.success().unwrap(), b"123");
+    let error = parser.parse(b"1:").failure().unwrap();
+    assert!(error.is_in_complete());
+  }
+}
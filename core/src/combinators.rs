@@ -1,12 +1,34 @@
+mod binary_combinators;
+mod bit_combinators;
 mod elements_combinators;
+mod keyword_combinators;
+mod length_combinators;
+mod lexeme_combinators;
+mod number_combinators;
 mod offset_combinators;
+mod permutation_combinators;
+mod regex_combinators;
 mod skip_combinators;
+mod string_combinators;
 mod take_combinators;
+mod token_combinators;
+mod unicode_combinators;
 
+pub use binary_combinators::*;
+pub use bit_combinators::*;
 pub use elements_combinators::*;
+pub use keyword_combinators::*;
+pub use length_combinators::*;
+pub use lexeme_combinators::*;
+pub use number_combinators::*;
 pub use offset_combinators::*;
+pub use permutation_combinators::*;
+pub use regex_combinators::*;
 pub use skip_combinators::*;
+pub use string_combinators::*;
 pub use take_combinators::*;
+pub use token_combinators::*;
+pub use unicode_combinators::*;
 
 use crate::prelude::*;
 use std::fmt::{Debug, Display};
@@ -0,0 +1,430 @@
+use crate::prelude::*;
+
+/// A fixed-width number that can be decoded from bytes.
+pub trait BinaryNumber: Sized {
+  /// The width in bytes.
+  const SIZE: usize;
+
+  /// Decode from exactly [BinaryNumber::SIZE] big-endian bytes.
+  fn from_be_slice(bytes: &[u8]) -> Self;
+
+  /// Decode from exactly [BinaryNumber::SIZE] little-endian bytes.
+  fn from_le_slice(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_binary_number {
+  ( $($t:ty),+ ) => {
+    $(
+      impl BinaryNumber for $t {
+        const SIZE: usize = std::mem::size_of::<$t>();
+
+        fn from_be_slice(bytes: &[u8]) -> Self {
+          <$t>::from_be_bytes(bytes.try_into().unwrap())
+        }
+
+        fn from_le_slice(bytes: &[u8]) -> Self {
+          <$t>::from_le_bytes(bytes.try_into().unwrap())
+        }
+      }
+    )+
+  };
+}
+
+impl_binary_number!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// A byte order, used with [endian_number].
+pub trait Endian {
+  /// Decode `T` from exactly [BinaryNumber::SIZE] bytes.
+  fn decode<T: BinaryNumber>(bytes: &[u8]) -> T;
+}
+
+/// Big-endian (network) byte order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BigEndian;
+
+/// Little-endian byte order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LittleEndian;
+
+impl Endian for BigEndian {
+  fn decode<T: BinaryNumber>(bytes: &[u8]) -> T {
+    T::from_be_slice(bytes)
+  }
+}
+
+impl Endian for LittleEndian {
+  fn decode<T: BinaryNumber>(bytes: &[u8]) -> T {
+    T::from_le_slice(bytes)
+  }
+}
+
+/// Returns a [ClonableParser] that parses a fixed-width number in the byte order `E`.
+///
+/// If the input is too short, fails with [ParseError::IncompleteNeeded] carrying the number of missing bytes.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0x01, 0x02, 0x03];
+///
+/// let parser = endian_number::<u16, BigEndian>();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), 0x0102);
+///
+/// let result = endian_number::<u32, LittleEndian>().parse(&input);
+///
+/// assert_eq!(result.failure().unwrap().needed(), Some(1));
+/// ```
+pub fn endian_number<'a, T, E>() -> Parser<'a, u8, T, impl Fn(ParseContext<'a, u8>) -> ParseResult<'a, u8, T> + 'a>
+where
+  T: BinaryNumber + 'a,
+  E: Endian + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, u8>| {
+    let input = parse_context.input();
+    if input.len() < T::SIZE {
+      let pe = ParseError::of_in_complete_needed(T::SIZE - input.len());
+      return ParseResult::failed_with_uncommitted(parse_context, pe);
+    }
+    let value = E::decode::<T>(&input[..T::SIZE]);
+    ParseResult::successful(parse_context, value, T::SIZE)
+  })
+}
+
+macro_rules! define_number_parsers {
+  ( $( ($name:ident, $t:ty, $endian:ty, $desc:literal) ),+ ) => {
+    $(
+      #[doc = concat!("Returns a [ClonableParser] that parses ", $desc, ". See [endian_number].")]
+      pub fn $name<'a>() -> Parser<'a, u8, $t, impl Fn(ParseContext<'a, u8>) -> ParseResult<'a, u8, $t> + 'a> {
+        endian_number::<$t, $endian>()
+      }
+    )+
+  };
+}
+
+define_number_parsers!(
+  (be_u8, u8, BigEndian, "an unsigned 8-bit integer"),
+  (be_u16, u16, BigEndian, "a big-endian unsigned 16-bit integer"),
+  (be_u32, u32, BigEndian, "a big-endian unsigned 32-bit integer"),
+  (be_u64, u64, BigEndian, "a big-endian unsigned 64-bit integer"),
+  (be_u128, u128, BigEndian, "a big-endian unsigned 128-bit integer"),
+  (be_i8, i8, BigEndian, "a signed 8-bit integer"),
+  (be_i16, i16, BigEndian, "a big-endian signed 16-bit integer"),
+  (be_i32, i32, BigEndian, "a big-endian signed 32-bit integer"),
+  (be_i64, i64, BigEndian, "a big-endian signed 64-bit integer"),
+  (be_i128, i128, BigEndian, "a big-endian signed 128-bit integer"),
+  (be_f32, f32, BigEndian, "a big-endian 32-bit float"),
+  (be_f64, f64, BigEndian, "a big-endian 64-bit float"),
+  (le_u8, u8, LittleEndian, "an unsigned 8-bit integer"),
+  (le_u16, u16, LittleEndian, "a little-endian unsigned 16-bit integer"),
+  (le_u32, u32, LittleEndian, "a little-endian unsigned 32-bit integer"),
+  (le_u64, u64, LittleEndian, "a little-endian unsigned 64-bit integer"),
+  (le_u128, u128, LittleEndian, "a little-endian unsigned 128-bit integer"),
+  (le_i8, i8, LittleEndian, "a signed 8-bit integer"),
+  (le_i16, i16, LittleEndian, "a little-endian signed 16-bit integer"),
+  (le_i32, i32, LittleEndian, "a little-endian signed 32-bit integer"),
+  (le_i64, i64, LittleEndian, "a little-endian signed 64-bit integer"),
+  (le_i128, i128, LittleEndian, "a little-endian signed 128-bit integer"),
+  (le_f32, f32, LittleEndian, "a little-endian 32-bit float"),
+  (le_f64, f64, LittleEndian, "a little-endian 64-bit float")
+);
+
+/// An unsigned integer that can be decoded from ULEB128. See [uleb128].
+pub trait UnsignedVarInt: Sized {
+  /// The width in bits.
+  const BITS: u32;
+
+  /// Truncate a decoded value that is known to fit.
+  fn from_u128(value: u128) -> Self;
+}
+
+/// A signed integer that can be decoded from SLEB128. See [sleb128].
+pub trait SignedVarInt: Sized {
+  /// The width in bits.
+  const BITS: u32;
+
+  /// Truncate a decoded two's complement value that is known to fit.
+  fn from_u128(value: u128) -> Self;
+}
+
+/// An unsigned integer with a zigzag-encoded signed counterpart. See [zigzag].
+pub trait ZigZag {
+  /// The signed counterpart.
+  type Signed;
+
+  /// Decode `(n >> 1) ^ -(n & 1)`.
+  fn zigzag_decode(self) -> Self::Signed;
+}
+
+macro_rules! impl_var_int {
+  ( $( ($u:ty, $i:ty) ),+ ) => {
+    $(
+      impl UnsignedVarInt for $u {
+        const BITS: u32 = <$u>::BITS;
+
+        fn from_u128(value: u128) -> Self {
+          value as $u
+        }
+      }
+
+      impl SignedVarInt for $i {
+        const BITS: u32 = <$i>::BITS;
+
+        fn from_u128(value: u128) -> Self {
+          value as $i
+        }
+      }
+
+      impl ZigZag for $u {
+        type Signed = $i;
+
+        fn zigzag_decode(self) -> $i {
+          (self >> 1) as $i ^ -((self & 1) as $i)
+        }
+      }
+    )+
+  };
+}
+
+impl_var_int!((u8, i8), (u16, i16), (u32, i32), (u64, i64), (u128, i128));
+
+enum VarIntError {
+  Incomplete,
+  Overflow(usize),
+  Overlong(usize),
+}
+
+/// Decodes a LEB128 value of at most `bits` bits, returning the raw two's complement bits and the encoded length.
+fn decode_leb128(input: &[u8], bits: u32, signed: bool) -> Result<(u128, usize), VarIntError> {
+  let max_len = bits.div_ceil(7) as usize;
+  let mut value = 0u128;
+  for (index, byte) in input.iter().enumerate() {
+    let payload = u128::from(byte & 0x7F);
+    let shift = 7 * index as u32;
+    let last = byte & 0x80 == 0;
+    if !last && index + 1 == max_len {
+      return Err(VarIntError::Overflow(index + 1));
+    }
+    if last {
+      // Bits of the last byte beyond the width must be zero, or copies of the sign bit if signed.
+      let avail = bits - shift;
+      if avail < 7 {
+        let upper = if signed {
+          payload >> (avail - 1)
+        } else {
+          payload >> avail
+        };
+        let all_ones = (1 << (7 - avail + u32::from(signed))) - 1;
+        if upper != 0 && !(signed && upper == all_ones) {
+          return Err(VarIntError::Overflow(index + 1));
+        }
+      }
+      if index > 0 {
+        let sign_of_prev = input[index - 1] & 0x40 != 0;
+        let redundant = if signed {
+          (*byte == 0x00 && !sign_of_prev) || (*byte == 0x7F && sign_of_prev)
+        } else {
+          *byte == 0x00
+        };
+        if redundant {
+          return Err(VarIntError::Overlong(index + 1));
+        }
+      }
+      value |= payload << shift;
+      if signed && shift + 7 < 128 && payload & 0x40 != 0 {
+        value |= !0u128 << (shift + 7);
+      }
+      return Ok((value, index + 1));
+    }
+    value |= payload << shift;
+  }
+  Err(VarIntError::Incomplete)
+}
+
+fn leb128<'a, T>(
+  name: &'static str,
+  bits: u32,
+  signed: bool,
+  f: fn(u128) -> T,
+) -> Parser<'a, u8, T, impl Fn(ParseContext<'a, u8>) -> ParseResult<'a, u8, T> + 'a>
+where
+  T: 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, u8>| {
+    let offset = parse_context.next_offset();
+    let (msg, length) = match decode_leb128(parse_context.input(), bits, signed) {
+      Ok((value, length)) => return ParseResult::successful(parse_context, f(value), length),
+      Err(VarIntError::Incomplete) => {
+        return ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete_needed(1))
+      }
+      Err(VarIntError::Overflow(length)) => (format!("{} overflows {} bits", name, bits), length),
+      Err(VarIntError::Overlong(length)) => (format!("overlong {} encoding", name), length),
+    };
+    let pe = ParseError::of_conversion(parse_context.original_input(), offset, length, msg);
+    ParseResult::failed_with_uncommitted(parse_context, pe)
+  })
+}
+
+/// Returns a [ClonableParser] that parses an unsigned LEB128 integer.
+///
+/// Encodings longer than necessary and values that do not fit in `T` fail with [ParseError::Conversion].
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0xE5, 0x8E, 0x26];
+///
+/// let parser = uleb128::<u32>();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), 624485);
+///
+/// assert!(uleb128::<u32>().parse(&[0x80, 0x00]).failure().unwrap().is_conversion());
+/// ```
+pub fn uleb128<'a, T>() -> Parser<'a, u8, T, impl Fn(ParseContext<'a, u8>) -> ParseResult<'a, u8, T> + 'a>
+where
+  T: UnsignedVarInt + 'a, {
+  leb128("uleb128", T::BITS, false, T::from_u128)
+}
+
+/// Returns a [ClonableParser] that parses a signed LEB128 integer.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0xC0, 0xBB, 0x78];
+///
+/// let parser = sleb128::<i64>();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), -123456);
+/// ```
+pub fn sleb128<'a, T>() -> Parser<'a, u8, T, impl Fn(ParseContext<'a, u8>) -> ParseResult<'a, u8, T> + 'a>
+where
+  T: SignedVarInt + 'a, {
+  leb128("sleb128", T::BITS, true, T::from_u128)
+}
+
+/// Returns a [ClonableParser] that parses a protobuf varint.
+///
+/// The encoding is the same as [uleb128]; use [zigzag] for `sint32` and `sint64` fields.
+///
+/// As in protobuf, the value is decoded as 64 bits and truncated to `T`. A negative `int32` is sign-extended to a
+/// 10-byte varint, so `varint::<u32>()` accepts it and `as i32` recovers the value.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01];
+///
+/// let parser = varint::<u32>();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap() as i32, -1);
+/// ```
+pub fn varint<'a, T>() -> Parser<'a, u8, T, impl Fn(ParseContext<'a, u8>) -> ParseResult<'a, u8, T> + 'a>
+where
+  T: UnsignedVarInt + 'a, {
+  leb128("varint", T::BITS.max(u64::BITS), false, T::from_u128)
+}
+
+/// Returns a [ClonableParser] that decodes the zigzag-encoded result of `parser`.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0x03];
+///
+/// let parser = zigzag(varint::<u64>());
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), -2);
+/// ```
+pub fn zigzag<'a, A, P>(parser: P) -> Parser<'a, u8, A::Signed, impl ParserFn<'a, u8, A::Signed>>
+where
+  A: ZigZag + 'a,
+  A::Signed: Clone + 'a,
+  P: ParserRunner<'a, u8, A> + 'a, {
+  parser.map(ZigZag::zigzag_decode)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_fixed_width_numbers() {
+    let input = [0xFF, 0xFE, 0x00, 0x00, 0x80, 0x3F];
+
+    let parser = be_i16() + le_f32();
+
+    let result = parser.parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.success().unwrap(), (-2, 1.0));
+  }
+
+  #[test]
+  fn test_number_incomplete() {
+    let input = [0x00; 5];
+
+    let result = (be_u8() * be_f64()).parse(&input);
+
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Uncommitted));
+    assert_eq!(result.failure().unwrap(), ParseError::of_in_complete_needed(4));
+  }
+
+  #[test]
+  fn test_leb128_bounds() {
+    assert_eq!(uleb128::<u8>().parse(&[0xFF, 0x01]).success().unwrap(), 255);
+    assert!(uleb128::<u8>().parse(&[0xFF, 0x02]).failure().unwrap().is_conversion());
+    assert!(uleb128::<u8>()
+      .parse(&[0x80, 0x80, 0x01])
+      .failure()
+      .unwrap()
+      .is_conversion());
+    assert_eq!(sleb128::<i8>().parse(&[0x80, 0x7F]).success().unwrap(), -128);
+    assert!(sleb128::<i8>().parse(&[0xFF, 0x7E]).failure().unwrap().is_conversion());
+    assert_eq!(sleb128::<i8>().parse(&[0x40]).success().unwrap(), -64);
+    assert!(sleb128::<i32>().parse(&[0xFF, 0x7F]).failure().unwrap().is_conversion());
+    assert_eq!(sleb128::<i32>().parse(&[0xC0, 0x00]).success().unwrap(), 64);
+    assert_eq!(
+      uleb128::<u64>().parse(&[0x80]).failure().unwrap(),
+      ParseError::of_in_complete_needed(1)
+    );
+  }
+
+  #[test]
+  fn test_varint_round_trip_extremes() {
+    let max = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01];
+    let min = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7F];
+
+    assert_eq!(varint::<u64>().parse(&max).success().unwrap(), u64::MAX);
+    assert_eq!(zigzag(varint::<u64>()).parse(&max).success().unwrap(), i64::MIN);
+    assert_eq!(sleb128::<i64>().parse(&min).success().unwrap(), i64::MIN);
+    let int32_min = [0x80, 0x80, 0x80, 0x80, 0xF8, 0xFF, 0xFF, 0xFF, 0xFF, 0x01];
+    assert_eq!(varint::<u32>().parse(&int32_min).success().unwrap() as i32, i32::MIN);
+    assert_eq!(varint::<u8>().parse(&[0xAC, 0x02]).success().unwrap(), 0x2C);
+    let i128_min = [[0x80; 18].as_slice(), &[0x7E]].concat();
+    assert_eq!(sleb128::<i128>().parse(&i128_min).success().unwrap(), i128::MIN);
+  }
+}
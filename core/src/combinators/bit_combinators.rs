@@ -0,0 +1,290 @@
+use crate::prelude::*;
+
+/// A bit of a byte slice, the input element of bit-level parsers.
+///
+/// Bit-level parsers read the bytes held by their [ParseContext], and offsets and lengths of bit-level parsers count
+/// bits. Bits are numbered from the most significant bit of each byte. `Bit` values carry no data, so run bit-level
+/// parsers on bytes with [BitParser::parse_bits] or [bit_stream].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Bit {
+  _private: (),
+}
+
+/// The elements of every bit-level input, which take no memory.
+static BITS: [Bit; usize::MAX] = [Bit { _private: () }; usize::MAX];
+
+/// Returns a context reading the bits of `bytes` from the bit `offset`.
+fn bit_context(bytes: &[u8], offset: usize) -> ParseContext<'_, Bit> {
+  ParseContext::with_bytes(&BITS[..bytes.len() * 8], bytes, offset)
+}
+
+/// Runs bit-level parsers on bytes.
+pub trait BitParser<'a, A>: ParserRunner<'a, Bit, A> {
+  /// Parses the bits of `bytes`, starting at the most significant bit of the first byte.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let input = [0xFF, 0b1010_0000];
+  ///
+  /// let parser = bool_bit() + bits(3);
+  ///
+  /// let result = parser.parse_bits(&input[1..]);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), (true, 0b010));
+  /// ```
+  fn parse_bits(&self, bytes: &'a [u8]) -> ParseResult<'a, Bit, A> {
+    self.run(bit_context(bytes, 0))
+  }
+}
+
+impl<'a, A, P> BitParser<'a, A> for P where P: ParserRunner<'a, Bit, A> {}
+
+fn read_bits(bytes: &[u8], offset: usize, n: usize) -> u64 {
+  (offset..offset + n).fold(0, |value, index| {
+    let bit = (bytes[index / 8] >> (7 - index % 8)) & 1;
+    (value << 1) | u64::from(bit)
+  })
+}
+
+/// Converts an error of a bit-level parser on the bits of `bytes` into an error on the bytes.
+fn bit_error_to_byte<'a>(error: ParseError<'a, Bit>, bytes: &'a [u8]) -> ParseError<'a, u8> {
+  let span = |offset: usize, length: usize| (offset / 8, (offset + length).div_ceil(8) - offset / 8);
+  match error {
+    ParseError::Mismatch {
+      input,
+      offset,
+      length,
+      message,
+    } => {
+      let (offset, length) = span(offset, length);
+      ParseError::of_mismatch(&bytes[..input.len().div_ceil(8)], offset, length, message)
+    }
+    ParseError::Conversion {
+      input,
+      offset,
+      length,
+      message,
+    } => {
+      let (offset, length) = span(offset, length);
+      ParseError::of_conversion(&bytes[..input.len().div_ceil(8)], offset, length, message)
+    }
+    ParseError::Incomplete => ParseError::of_in_complete(),
+    ParseError::IncompleteNeeded { needed } => ParseError::of_in_complete_needed(needed.div_ceil(8)),
+    ParseError::Expect { offset, inner, message } => {
+      ParseError::of_expect(offset / 8, Box::new(bit_error_to_byte(*inner, bytes)), message)
+    }
+    ParseError::Custom { offset, inner, message } => ParseError::of_custom(
+      offset / 8,
+      inner.map(|inner| Box::new(bit_error_to_byte(*inner, bytes))),
+      message,
+    ),
+    ParseError::Permutation { offset, index, kind } => ParseError::of_permutation(offset / 8, index, kind),
+  }
+}
+
+/// Converts an error of a byte-level parser into an error on the bits.
+fn byte_error_to_bit<'a>(error: ParseError<'a, u8>) -> ParseError<'a, Bit> {
+  match error {
+    ParseError::Mismatch {
+      input,
+      offset,
+      length,
+      message,
+    } => ParseError::of_mismatch(&BITS[..input.len() * 8], offset * 8, length * 8, message),
+    ParseError::Conversion {
+      input,
+      offset,
+      length,
+      message,
+    } => ParseError::of_conversion(&BITS[..input.len() * 8], offset * 8, length * 8, message),
+    ParseError::Incomplete => ParseError::of_in_complete(),
+    ParseError::IncompleteNeeded { needed } => ParseError::of_in_complete_needed(needed * 8),
+    ParseError::Expect { offset, inner, message } => {
+      ParseError::of_expect(offset * 8, Box::new(byte_error_to_bit(*inner)), message)
+    }
+    ParseError::Custom { offset, inner, message } => ParseError::of_custom(
+      offset * 8,
+      inner.map(|inner| Box::new(byte_error_to_bit(*inner))),
+      message,
+    ),
+    ParseError::Permutation { offset, index, kind } => ParseError::of_permutation(offset * 8, index, kind),
+  }
+}
+
+/// Returns a [ClonableParser] that parses `n` bits as an unsigned integer, most significant bit first.
+///
+/// - n: the number of bits, at most 64
+///
+/// # Panics
+///
+/// Panics if `n` is greater than 64.
+pub fn bits<'a>(n: usize) -> Parser<'a, Bit, u64, impl Fn(ParseContext<'a, Bit>) -> ParseResult<'a, Bit, u64> + 'a> {
+  assert!(n <= 64, "bits: cannot read {} bits into u64", n);
+  Parser::new(move |parse_context: ParseContext<'a, Bit>| {
+    let remaining = parse_context.remaining();
+    if remaining < n {
+      return ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete_needed(n - remaining));
+    }
+    let value = read_bits(parse_context.bytes(), parse_context.next_offset(), n);
+    ParseResult::successful(parse_context, value, n)
+  })
+}
+
+/// Returns a [ClonableParser] that parses one bit as a `bool`.
+pub fn bool_bit<'a>() -> Parser<'a, Bit, bool, impl Fn(ParseContext<'a, Bit>) -> ParseResult<'a, Bit, bool> + 'a> {
+  bits(1).map(|bit| bit == 1)
+}
+
+/// Returns a [ClonableParser] that skips to the next byte boundary.
+pub fn align_to_byte<'a>() -> Parser<'a, Bit, (), impl Fn(ParseContext<'a, Bit>) -> ParseResult<'a, Bit, ()> + 'a> {
+  Parser::new(move |parse_context: ParseContext<'a, Bit>| {
+    let padding = (8 - parse_context.next_offset() % 8) % 8;
+    ParseResult::successful(parse_context, (), padding)
+  })
+}
+
+/// Returns a [ClonableParser] that runs the bit-level `parser` on bytes.
+///
+/// Parsing starts at the most significant bit of the current byte. A partially consumed last byte is skipped, so
+/// the byte-level grammar continues at a byte boundary. Error offsets are converted to bytes.
+///
+/// - parser: a bit-level parser
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// // A 3-bit version, a 13-bit length, then a byte-level payload
+/// let input = [0b0100_0000, 0x02, b'h', b'i'];
+///
+/// let header = bit_stream(bits(3) + bits(13));
+/// let parser = header.flat_map(|(version, length)| take(length as usize).map(move |payload| (version, payload)));
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), (2, &b"hi"[..]));
+/// ```
+pub fn bit_stream<'a, A, P>(
+  parser: P,
+) -> Parser<'a, u8, A, impl Fn(ParseContext<'a, u8>) -> ParseResult<'a, u8, A> + 'a>
+where
+  A: 'a,
+  P: ParserRunner<'a, Bit, A> + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, u8>| {
+    let bytes = parse_context.original_input();
+    match parser.run(bit_context(bytes, parse_context.next_offset() * 8)) {
+      ParseResult::Success { value, length, .. } => ParseResult::successful(parse_context, value, length.div_ceil(8)),
+      ParseResult::Failure {
+        error,
+        committed_status,
+        ..
+      } => ParseResult::failed(parse_context, bit_error_to_byte(error, bytes), committed_status),
+    }
+  })
+}
+
+/// Returns a [ClonableParser] that runs the byte-level `parser` inside a bit-level grammar.
+///
+/// Fails unless the current position is on a byte boundary; see [align_to_byte].
+///
+/// - parser: a byte-level parser
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0b1000_0000, 0x01, 0x02];
+///
+/// let parser = bool_bit() - align_to_byte() + byte_aligned(be_u16());
+///
+/// let result = parser.parse_bits(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), (true, 0x0102));
+/// assert!((bool_bit() * byte_aligned(be_u16())).parse_bits(&input).is_failure());
+/// ```
+pub fn byte_aligned<'a, A, P>(
+  parser: P,
+) -> Parser<'a, Bit, A, impl Fn(ParseContext<'a, Bit>) -> ParseResult<'a, Bit, A> + 'a>
+where
+  A: 'a,
+  P: ParserRunner<'a, u8, A> + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, Bit>| {
+    let offset = parse_context.next_offset();
+    if !offset.is_multiple_of(8) {
+      let msg = format!("expect byte boundary, found bit offset: {}", offset);
+      let pe = ParseError::of_mismatch(parse_context.original_input(), offset, 0, msg);
+      return ParseResult::failed_with_uncommitted(parse_context, pe);
+    }
+    // A bounded bit input bounds the bytes too
+    let bytes = parse_context.bytes();
+    let byte_context = ParseContext::new(&bytes[..parse_context.total_length() / 8], offset / 8);
+    match parser.run(byte_context) {
+      ParseResult::Success { value, length, .. } => ParseResult::successful(parse_context, value, length * 8),
+      ParseResult::Failure {
+        error,
+        committed_status,
+        ..
+      } => ParseResult::failed(parse_context, byte_error_to_bit(error), committed_status),
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_bits_across_bytes() {
+    let input = [0b1011_0110, 0b0101_1111];
+    let parser = bits(3) + bits(9) + bool_bit().of_many0();
+
+    let result = parser.parse_bits(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 16);
+    assert_eq!(result.success().unwrap(), ((0b101, 0b1_0110_0101), vec![true; 4]));
+  }
+
+  #[test]
+  fn test_bit_stream_errors_are_in_bytes() {
+    let input = [0xFF, 0x00];
+
+    let result = (be_u8() * bit_stream(bits(4) * bits(13))).parse(&input);
+    assert_eq!(result.failure().unwrap(), ParseError::of_in_complete_needed(2));
+
+    let parser = be_u8() * bit_stream(bits(4) * byte_aligned(be_u8()));
+    let error = parser.parse(&input).failure().unwrap();
+    assert_eq!(error.input().unwrap(), &[0x00]);
+  }
+
+  #[test]
+  fn test_bits_of_sub_slices() {
+    let input = [0x03, 0xE0];
+
+    assert_eq!(bits(8).parse_bits(&input[1..]).success().unwrap(), 0xE0);
+    assert_eq!((bits(4) * bits(8)).parse_bits(&input).success().unwrap(), 0x3E);
+  }
+
+  #[test]
+  fn test_length_value_on_bits() {
+    let input = [0x03, 0xE0];
+
+    let result = length_value(bits(8), bits(3)).parse_bits(&input);
+    assert_eq!(result.consumed_count(), 11);
+    assert_eq!(result.success().unwrap(), 0b111);
+
+    let result = length_value(bits(8), bits(4)).parse_bits(&input);
+    assert!(result.is_failure());
+
+    let result = length_value(bits(8), byte_aligned(be_u8())).parse_bits(&[0x08, 0xAB]);
+    assert_eq!(result.success().unwrap(), 0xAB);
+  }
+}
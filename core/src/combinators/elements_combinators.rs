@@ -1,6 +1,5 @@
 use crate::prelude::*;
 use crate::util::Set;
-use regex::Regex;
 use std::fmt::{Debug, Display};
 
 /// Returns a [ClonableParser] that parses the elements that satisfy the specified closure conditions.(for reference)
@@ -557,52 +556,87 @@ where
   })
 }
 
-/// Returns a [ClonableParser] that parses a string that match a regular expression.
+/// Returns a [ClonableParser] that parses a sequence of bytes.
 ///
-/// - pattern: a regular expression
+/// Unlike [seq], the result is the matched slice of the input.
+///
+/// - tag: a byte string
 ///
 /// # Example
 ///
 /// ```rust
 /// # use twill_core::prelude::*;
 ///
-/// let text: &str = "abcdef";
-/// let input = text.chars().collect::<Vec<_>>();
+/// let input = b"GET / HTTP/1.1";
 ///
-/// let parser = regex("[abc]+");
+/// let parser = tag_bytes(b"GET");
 ///
-/// let result = parser.parse(&input);
+/// let result = parser.parse(input);
 ///
 /// assert!(result.is_success());
-/// assert_eq!(result.success().unwrap(), "abc");
+/// assert_eq!(result.success().unwrap(), b"GET");
 /// ```
-pub fn regex<'a>(
-  pattern: &str,
-) -> Parser<'a, char, String, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, String> + 'a> {
-  let pattern = if !pattern.starts_with("^") {
-    format!("^{}", pattern)
-  } else {
-    pattern.to_string()
-  };
-  let regex = Regex::new(&pattern).unwrap();
-  Parser::new(move |parse_context| {
-    let input: &[char] = parse_context.input();
-    log::debug!("regex: input = {:?}", input);
-    let str = String::from_iter(input);
-    if let Some(captures) = regex.captures(&str).as_ref() {
-      match captures.get(0) {
-        Some(m) => ParseResult::successful(parse_context, m.as_str().to_string(), m.as_str().len()),
-        _ => {
-          let msg = format!("regex {:?} found: {:?}", regex, str);
-          let input = parse_context.input();
+pub fn tag_bytes<'a, 'b>(tag: &'b [u8]) -> Parser<'a, u8, &'a [u8], impl ParserFn<'a, u8, &'a [u8]>>
+where
+  'b: 'a, {
+  tag_bytes_by(tag, "tag_bytes", |expected, actual| expected == actual)
+}
+
+/// Returns a [ClonableParser] that parses a sequence of bytes, ignoring ASCII case.
+///
+/// - tag: a byte string
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = b"Content-Length: 3";
+///
+/// let parser = tag_no_case_bytes(b"content-length");
+///
+/// let result = parser.parse(input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), b"Content-Length");
+/// ```
+pub fn tag_no_case_bytes<'a, 'b>(tag: &'b [u8]) -> Parser<'a, u8, &'a [u8], impl ParserFn<'a, u8, &'a [u8]>>
+where
+  'b: 'a, {
+  tag_bytes_by(tag, "tag_no_case_bytes", |expected, actual| {
+    expected.eq_ignore_ascii_case(actual)
+  })
+}
+
+fn tag_bytes_by<'a, 'b, F>(
+  tag: &'b [u8],
+  name: &'static str,
+  eq: F,
+) -> Parser<'a, u8, &'a [u8], impl ParserFn<'a, u8, &'a [u8]>>
+where
+  'b: 'a,
+  F: Fn(&u8, &u8) -> bool + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, u8>| {
+    let input = parse_context.input();
+    for (index, expected) in tag.iter().enumerate() {
+      match input.get(index) {
+        Some(actual) if eq(expected, actual) => {}
+        Some(actual) => {
+          let msg = format!(
+            "{} {:?} expect: {:?}, found: {:?}",
+            name,
+            String::from_utf8_lossy(tag),
+            char::from(*expected),
+            char::from(*actual)
+          );
           let offset = parse_context.next_offset();
-          let pe = ParseError::of_mismatch(input, offset, str.len(), msg);
-          ParseResult::failed(parse_context, pe, (captures.len() != 0).into())
+          let pe = ParseError::of_mismatch(parse_context.original_input(), offset, index + 1, msg);
+          return ParseResult::failed(parse_context, pe, (index != 0).into());
         }
+        None => return ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete()),
       }
-    } else {
-      ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete())
     }
+    ParseResult::successful(parse_context, &input[..tag.len()], tag.len())
   })
 }
 
@@ -675,4 +709,19 @@ mod tests {
     assert_eq!(result.consumed_count(), 0);
     assert!(result.failure().unwrap().is_in_complete());
   }
+
+  #[test]
+  fn test_tag_bytes_failure() {
+    let input = b"GEX";
+
+    let result = tag_bytes(b"GET").parse(input);
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Committed));
+    assert_eq!(result.failure().unwrap().input_string().unwrap(), "GEX");
+
+    let result = tag_bytes(b"GET").parse(&input[..2]);
+    assert!(result.failure().unwrap().is_in_complete());
+
+    let result = tag_no_case_bytes(b"gex").parse(input);
+    assert_eq!(result.consumed_count(), 3);
+  }
 }
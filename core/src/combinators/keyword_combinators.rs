@@ -0,0 +1,240 @@
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+  children: Vec<(char, usize)>,
+  entry: Option<usize>,
+}
+
+/// A trie of string literals, matched in one pass over the input.
+#[derive(Debug, Clone)]
+struct Trie {
+  nodes: Vec<TrieNode>,
+  ignore_case: bool,
+}
+
+impl Trie {
+  fn new<'b>(literals: impl IntoIterator<Item = &'b str>, ignore_case: bool) -> Self {
+    let mut trie = Self {
+      nodes: vec![TrieNode::default()],
+      ignore_case,
+    };
+    for (entry, literal) in literals.into_iter().enumerate() {
+      let mut node = 0;
+      for c in literal.chars() {
+        let c = trie.fold(c);
+        node = match trie.nodes[node].children.binary_search_by_key(&c, |(k, _)| *k) {
+          Ok(index) => trie.nodes[node].children[index].1,
+          Err(index) => {
+            let child = trie.nodes.len();
+            trie.nodes.push(TrieNode::default());
+            trie.nodes[node].children.insert(index, (c, child));
+            child
+          }
+        };
+      }
+      // The first of duplicated literals wins, as with alternation.
+      trie.nodes[node].entry.get_or_insert(entry);
+    }
+    trie
+  }
+
+  fn fold(&self, c: char) -> char {
+    if self.ignore_case {
+      c.to_ascii_lowercase()
+    } else {
+      c
+    }
+  }
+
+  /// Returns the entry and length of the longest literal at the start of `input`, or the length of the longest
+  /// prefix of any literal on failure.
+  fn longest_match(&self, input: &[char]) -> Result<(usize, usize), usize> {
+    let mut node = 0;
+    let mut matched = self.nodes[0].entry.map(|entry| (entry, 0));
+    let mut depth = 0;
+    for c in input {
+      let c = self.fold(*c);
+      match self.nodes[node].children.binary_search_by_key(&c, |(k, _)| *k) {
+        Ok(index) => node = self.nodes[node].children[index].1,
+        Err(_) => break,
+      }
+      depth += 1;
+      if let Some(entry) = self.nodes[node].entry {
+        matched = Some((entry, depth));
+      }
+    }
+    matched.ok_or(depth)
+  }
+
+  fn parser<'a, A, F>(
+    self,
+    name: &'static str,
+    f: F,
+  ) -> Parser<'a, char, A, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, A> + 'a>
+  where
+    F: Fn(usize) -> A + 'a, {
+    Parser::new(move |parse_context: ParseContext<'a, char>| {
+      let input = parse_context.input();
+      match self.longest_match(input) {
+        Ok((entry, length)) => ParseResult::successful(parse_context, f(entry), length),
+        Err(depth) if depth == input.len() => {
+          ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete())
+        }
+        Err(depth) => {
+          let msg = format!("{} found: {:?}", name, String::from_iter(&input[..=depth]));
+          let offset = parse_context.next_offset();
+          let pe = ParseError::of_mismatch(parse_context.original_input(), offset, depth + 1, msg);
+          ParseResult::failed_with_uncommitted(parse_context, pe)
+        }
+      }
+    })
+  }
+}
+
+/// Returns a [ClonableParser] that parses the longest of the strings in `tags`, and returns its index.
+///
+/// The strings are compiled into a trie, so the input is scanned once regardless of the number of strings.
+///
+/// - tags: strings
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text: &str = "<=>";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = one_of_tags(&["<", "<=", "<=>", "="]);
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), 2);
+/// ```
+pub fn one_of_tags<'a>(
+  tags: &[&str],
+) -> Parser<'a, char, usize, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, usize> + 'a> {
+  Trie::new(tags.iter().copied(), false).parser("one_of_tags", |index| index)
+}
+
+/// Returns a [ClonableParser] that parses the longest of the strings in `tags`, ignoring ASCII case, and returns its
+/// index.
+///
+/// - tags: strings
+pub fn one_of_tags_no_case<'a>(
+  tags: &[&str],
+) -> Parser<'a, char, usize, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, usize> + 'a> {
+  Trie::new(tags.iter().copied(), true).parser("one_of_tags_no_case", |index| index)
+}
+
+/// Returns a [ClonableParser] that parses the longest of the keywords in `table`, and returns the mapped value.
+///
+/// If a keyword appears more than once, the first entry wins.
+///
+/// - table: pairs of a keyword and its value
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Op {
+///   Lt,
+///   Le,
+///   Ne,
+/// }
+///
+/// let text: &str = "<>";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = keyword_table(&[("<", Op::Lt), ("<=", Op::Le), ("<>", Op::Ne)]);
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), Op::Ne);
+/// ```
+pub fn keyword_table<'a, A>(
+  table: &[(&str, A)],
+) -> Parser<'a, char, A, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, A> + 'a>
+where
+  A: Clone + 'a, {
+  let values = table.iter().map(|(_, value)| value.clone()).collect::<Vec<_>>();
+  Trie::new(table.iter().map(|(keyword, _)| *keyword), false)
+    .parser("keyword_table", move |index| values[index].clone())
+}
+
+/// Returns a [ClonableParser] that parses the longest of the keywords in `table`, ignoring ASCII case, and returns the
+/// mapped value.
+///
+/// - table: pairs of a keyword and its value
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text: &str = "Select *";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = keyword_table_no_case(&[("SELECT", 1), ("SET", 2), ("FROM", 3)]);
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), 1);
+/// ```
+pub fn keyword_table_no_case<'a, A>(
+  table: &[(&str, A)],
+) -> Parser<'a, char, A, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, A> + 'a>
+where
+  A: Clone + 'a, {
+  let values = table.iter().map(|(_, value)| value.clone()).collect::<Vec<_>>();
+  Trie::new(table.iter().map(|(keyword, _)| *keyword), true)
+    .parser("keyword_table_no_case", move |index| values[index].clone())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_one_of_tags_backtracks_to_longest_match() {
+    let text = "abcx";
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = one_of_tags(&["a", "abcd", "ab"]);
+
+    let result = parser.parse(&input);
+
+    assert_eq!(result.consumed_count(), 2);
+    assert_eq!(result.success().unwrap(), 2);
+  }
+
+  #[test]
+  fn test_one_of_tags_failure() {
+    let text = "sex";
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = one_of_tags_no_case(&["SELECT", "SET"]);
+
+    let result = parser.parse(&input);
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Uncommitted));
+    assert_eq!(result.failure().unwrap().input_string().unwrap(), "sex");
+
+    let result = parser.parse(&input[..2]);
+    assert!(result.failure().unwrap().is_in_complete());
+  }
+
+  #[test]
+  fn test_keyword_table_first_duplicate_wins() {
+    let text = "if";
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = keyword_table(&[("if", 1), ("i", 2), ("if", 3)]);
+
+    let result = parser.parse(&input);
+
+    assert_eq!(result.success().unwrap(), 1);
+  }
+}
@@ -0,0 +1,239 @@
+use crate::prelude::*;
+use std::fmt::{Debug, Display};
+
+/// Runs the length parser, and returns the header length and the decoded length.
+fn run_length<'a, I, N, B, P>(
+  parser: &P,
+  parse_context: &ParseContext<'a, I>,
+) -> Result<(usize, usize), ParseResult<'a, I, B>>
+where
+  I: Debug + 'a,
+  N: TryInto<usize> + Display + Clone + 'a,
+  P: ParserRunner<'a, I, N>, {
+  match parser.run(parse_context.with_same_state()) {
+    ParseResult::Success { value, length, .. } => match value.clone().try_into() {
+      Ok(n) => Ok((length, n)),
+      Err(_) => {
+        let msg = format!("length {} does not fit in usize", value);
+        let offset = parse_context.next_offset();
+        let pe = ParseError::of_conversion(parse_context.original_input(), offset, length, msg);
+        Err(ParseResult::failed_with_uncommitted(
+          parse_context.with_same_state(),
+          pe,
+        ))
+      }
+    },
+    ParseResult::Failure {
+      parse_context,
+      error,
+      committed_status,
+    } => Err(ParseResult::failed(parse_context, error, committed_status)),
+  }
+}
+
+/// Runs `parser` on exactly the `n` elements at `body_context`, and returns its value.
+fn run_bounded<'a, I, A, P>(
+  name: &str,
+  parser: &P,
+  body_context: ParseContext<'a, I>,
+  n: usize,
+) -> Result<A, ParseResult<'a, I, A>>
+where
+  I: Debug + 'a,
+  P: ParserRunner<'a, I, A>, {
+  let offset = body_context.next_offset();
+  let original_input = body_context.original_input();
+  if body_context.remaining() < n {
+    let pe = ParseError::of_in_complete_needed(n - body_context.remaining());
+    return Err(ParseResult::failed_with_uncommitted(body_context, pe));
+  }
+  let msg = match parser.run(body_context.bounded(n)) {
+    ParseResult::Success { value, length, .. } if length == n => return Ok(value),
+    ParseResult::Success { length, .. } => format!("{} consumed {} of {} elements", name, length, n),
+    ParseResult::Failure { error, .. } if error.is_in_complete() => {
+      format!("{} ran past the end of {} elements", name, n)
+    }
+    ParseResult::Failure {
+      parse_context,
+      error,
+      committed_status,
+    } => return Err(ParseResult::failed(parse_context, error, committed_status)),
+  };
+  let pe = ParseError::of_mismatch(original_input, offset, n, msg);
+  Err(ParseResult::failed_with_commit(body_context, pe))
+}
+
+/// Returns a [ClonableParser] that parses a length with `length_parser`, and then returns that many elements.
+///
+/// If the input is shorter than the length, fails with [ParseError::IncompleteNeeded] carrying the number of missing
+/// elements.
+///
+/// - length_parser: a parser of the length
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0x00, 0x03, b'a', b'b', b'c', b'd'];
+///
+/// let parser = length_data(be_u16());
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), b"abc");
+/// ```
+pub fn length_data<'a, I, N, P>(length_parser: P) -> Parser<'a, I, &'a [I], impl ParserFn<'a, I, &'a [I]>>
+where
+  I: Debug + 'a,
+  N: TryInto<usize> + Display + Clone + 'a,
+  P: ParserRunner<'a, I, N> + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, I>| {
+    let (header_length, n) = match run_length(&length_parser, &parse_context) {
+      Ok(length) => length,
+      Err(failure) => return failure,
+    };
+    let body_context = parse_context.add_offset(header_length);
+    if body_context.remaining() < n {
+      let pe = ParseError::of_in_complete_needed(n - body_context.remaining());
+      return ParseResult::failed_with_uncommitted(parse_context, pe);
+    }
+    ParseResult::successful(parse_context, body_context.slice_with_len(n), header_length + n)
+  })
+}
+
+/// Returns a [ClonableParser] that parses a length with `length_parser`, and then runs `parser` on exactly that many
+/// elements.
+///
+/// `parser` sees an input that ends after the value, and must consume all of it.
+///
+/// - length_parser: a parser of the length
+/// - parser: a parser of the value
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0x04, 0x00, 0x01, 0x00, 0x02, 0xFF];
+///
+/// let parser = length_value(be_u8(), be_u16().of_many0());
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), vec![1, 2]);
+///
+/// let result = length_value(be_u8(), be_u8()).parse(&input);
+///
+/// assert!(result.is_failure());
+/// ```
+pub fn length_value<'a, I, N, A, P1, P2>(length_parser: P1, parser: P2) -> Parser<'a, I, A, impl ParserFn<'a, I, A>>
+where
+  I: Debug + 'a,
+  N: TryInto<usize> + Display + Clone + 'a,
+  A: 'a,
+  P1: ParserRunner<'a, I, N> + 'a,
+  P2: ParserRunner<'a, I, A> + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, I>| {
+    let (header_length, n) = match run_length(&length_parser, &parse_context) {
+      Ok(length) => length,
+      Err(failure) => return failure,
+    };
+    match run_bounded("length_value", &parser, parse_context.add_offset(header_length), n) {
+      Ok(value) => ParseResult::successful(parse_context, value, header_length + n),
+      Err(failure) => failure,
+    }
+  })
+}
+
+/// Returns a [ClonableParser] that parses a tag-length-value record.
+///
+/// `dispatch` receives the tag and returns the parser for the value, which must consume exactly the length.
+///
+/// - tag_parser: a parser of the tag
+/// - length_parser: a parser of the length
+/// - dispatch: a function from the tag to a parser of the value
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = [0x01, 0x02, 0xAA, 0xBB, 0x02, 0x00];
+///
+/// let field = tlv(be_u8(), be_u8(), |tag| elm_any().of_many0().map(move |value| (tag, value)));
+///
+/// let result = field.of_many0().parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), vec![(1, vec![0xAA, 0xBB]), (2, vec![])]);
+/// ```
+pub fn tlv<'a, I, T, N, A, P1, P2, P3, F>(
+  tag_parser: P1,
+  length_parser: P2,
+  dispatch: F,
+) -> Parser<'a, I, A, impl ParserFn<'a, I, A>>
+where
+  I: Debug + 'a,
+  T: 'a,
+  N: TryInto<usize> + Display + Clone + 'a,
+  A: 'a,
+  P1: ParserRunner<'a, I, T> + 'a,
+  P2: ParserRunner<'a, I, N> + 'a,
+  P3: ParserRunner<'a, I, A> + 'a,
+  F: Fn(T) -> P3 + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, I>| {
+    let (tag, tag_length) = match tag_parser.run(parse_context.with_same_state()) {
+      ParseResult::Success { value, length, .. } => (value, length),
+      ParseResult::Failure {
+        parse_context,
+        error,
+        committed_status,
+      } => return ParseResult::failed(parse_context, error, committed_status),
+    };
+    let length_context = parse_context.add_offset(tag_length);
+    let (header_length, n) = match run_length(&length_parser, &length_context) {
+      Ok(length) => length,
+      Err(failure) => return failure,
+    };
+    let body_context = length_context.add_offset(header_length);
+    match run_bounded("tlv", &dispatch(tag), body_context, n) {
+      Ok(value) => ParseResult::successful(parse_context, value, tag_length + header_length + n),
+      Err(failure) => failure,
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_length_value_error_offsets() {
+    let input = [0x00, 0x02, b'a', b'x', b'b'];
+    let parser = be_u8() * length_value(be_u8(), tag_bytes(b"ab"));
+
+    let result = parser.parse(&input);
+
+    assert!(result.is_failure());
+    let error = result.failure().unwrap();
+    assert!(error.is_mismatch());
+    assert_eq!(error.input().unwrap(), b"ax");
+  }
+
+  #[test]
+  fn test_length_value_inner_cannot_read_past_value() {
+    let input = [0x01, 0x00, 0x02];
+
+    let result = length_value(be_u8(), be_u16()).parse(&input);
+
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Committed));
+    assert_eq!(result.failure().unwrap().input().unwrap(), &[0x00]);
+
+    let result = length_data(be_u8()).parse(&input[..1]);
+
+    assert_eq!(result.failure().unwrap().needed(), Some(1));
+  }
+}
@@ -0,0 +1,276 @@
+use crate::prelude::*;
+
+/// A description of the trivia (whitespace and comments) between tokens.
+///
+/// Declare the trivia once and wrap tokens with [Lexer::lexeme], [Lexer::symbol] or [Lexer::keyword], which skip
+/// trailing trivia automatically.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text = "let /* a /* nested */ comment */ x // trailing\n = 1";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let lexer = Lexer::new().with_line_comment("//").with_nested_block_comment("/*", "*/");
+/// let ident = lexer.lexeme(elm_alpha_ref().of_many1().map(|cs| cs.into_iter().collect::<String>()));
+/// let parser = lexer.trivia() * lexer.keyword("let") * ident - lexer.symbol("=");
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), "x");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lexer {
+  whitespace: fn(char) -> bool,
+  identifier_char: fn(char) -> bool,
+  line_comments: Vec<String>,
+  block_comments: Vec<(String, String, bool)>,
+}
+
+impl Default for Lexer {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Lexer {
+  /// Create a lexer that skips Unicode whitespace and no comments.
+  ///
+  /// Identifier characters (for [Lexer::keyword]) default to alphanumerics and `_`.
+  pub fn new() -> Self {
+    Self {
+      whitespace: char::is_whitespace,
+      identifier_char: |c| c.is_alphanumeric() || c == '_',
+      line_comments: vec![],
+      block_comments: vec![],
+    }
+  }
+
+  /// Set the predicate for whitespace characters.
+  pub fn with_whitespace(mut self, f: fn(char) -> bool) -> Self {
+    self.whitespace = f;
+    self
+  }
+
+  /// Set the predicate for identifier characters, used to check keyword boundaries.
+  pub fn with_identifier_char(mut self, f: fn(char) -> bool) -> Self {
+    self.identifier_char = f;
+    self
+  }
+
+  /// Add a comment running from `start` to the end of the line.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `start` is empty.
+  pub fn with_line_comment(mut self, start: &str) -> Self {
+    assert!(!start.is_empty(), "line comment start must not be empty");
+    self.line_comments.push(start.to_string());
+    self
+  }
+
+  /// Add a block comment between `start` and `end` that does not nest.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `start` or `end` is empty.
+  pub fn with_block_comment(mut self, start: &str, end: &str) -> Self {
+    assert!(!start.is_empty() && !end.is_empty(), "block comment delimiters must not be empty");
+    self.block_comments.push((start.to_string(), end.to_string(), false));
+    self
+  }
+
+  /// Add a block comment between `start` and `end` that may contain nested block comments.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `start` or `end` is empty.
+  pub fn with_nested_block_comment(mut self, start: &str, end: &str) -> Self {
+    assert!(!start.is_empty() && !end.is_empty(), "block comment delimiters must not be empty");
+    self.block_comments.push((start.to_string(), end.to_string(), true));
+    self
+  }
+
+  /// Returns the length of the trivia at the start of `input`.
+  ///
+  /// On an unterminated block comment, returns the offset and length of the comment.
+  fn scan_trivia(&self, input: &[char]) -> Result<usize, (usize, usize, String)> {
+    let mut index = 0;
+    'outer: while index < input.len() {
+      if (self.whitespace)(input[index]) {
+        index += 1;
+        continue;
+      }
+      for start in &self.line_comments {
+        if starts_with(&input[index..], start) {
+          index += input[index..].iter().take_while(|c| **c != '\n').count();
+          continue 'outer;
+        }
+      }
+      for (start, end, nested) in &self.block_comments {
+        if starts_with(&input[index..], start) {
+          let comment_start = index;
+          let start_len = start.chars().count();
+          let end_len = end.chars().count();
+          let mut depth = 1;
+          index += start_len;
+          while depth > 0 {
+            if index >= input.len() {
+              let msg = format!("unterminated block comment, expect: {}", end);
+              return Err((comment_start, input.len() - comment_start, msg));
+            }
+            if starts_with(&input[index..], end) {
+              depth -= 1;
+              index += end_len;
+            } else if *nested && starts_with(&input[index..], start) {
+              depth += 1;
+              index += start_len;
+            } else {
+              index += 1;
+            }
+          }
+          continue 'outer;
+        }
+      }
+      break;
+    }
+    Ok(index)
+  }
+
+  /// Returns a [Parser] that skips zero or more whitespace characters and comments.
+  pub fn trivia<'a>(&self) -> Parser<'a, char, (), impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, ()> + 'a> {
+    let lexer = self.clone();
+    Parser::new(
+      move |parse_context: ParseContext<'a, char>| match lexer.scan_trivia(parse_context.input()) {
+        Ok(length) => ParseResult::successful(parse_context, (), length),
+        Err((offset, length, msg)) => {
+          let offset = parse_context.next_offset() + offset;
+          let pe = ParseError::of_mismatch(parse_context.original_input(), offset, length, msg);
+          ParseResult::failed_with_commit(parse_context, pe)
+        }
+      },
+    )
+  }
+
+  /// Returns a [Parser] that runs `parser` and then skips trailing trivia.
+  pub fn lexeme<'a, A, P>(
+    &self,
+    parser: P,
+  ) -> Parser<'a, char, A, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, A> + 'a>
+  where
+    A: Clone + 'a,
+    P: ParserRunner<'a, char, A> + 'a, {
+    parser.skip_right(self.trivia())
+  }
+
+  /// Returns a [Parser] that parses the string `symbol` and then skips trailing trivia.
+  pub fn symbol<'a, 'b>(
+    &self,
+    symbol: &'b str,
+  ) -> Parser<'a, char, String, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, String> + 'a>
+  where
+    'b: 'a, {
+    self.lexeme(tag(symbol))
+  }
+
+  /// Returns a [Parser] that parses the keyword `keyword` and then skips trailing trivia.
+  ///
+  /// The keyword must not be followed by an identifier character, so `keyword("if")` does not match `iffy`.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let lexer = Lexer::new();
+  /// let input = "iffy".chars().collect::<Vec<_>>();
+  ///
+  /// assert!(lexer.keyword("if").parse(&input).is_failure());
+  /// assert!(lexer.symbol("if").parse(&input).is_success());
+  /// ```
+  pub fn keyword<'a, 'b>(
+    &self,
+    keyword: &'b str,
+  ) -> Parser<'a, char, String, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, String> + 'a>
+  where
+    'b: 'a, {
+    let identifier_char = self.identifier_char;
+    let keyword_parser = Parser::new(move |parse_context: ParseContext<'a, char>| {
+      let length = keyword.chars().count();
+      match tag(keyword).run(parse_context.with_same_state()) {
+        ParseResult::Success { value, .. } => match parse_context.input().get(length) {
+          Some(c) if identifier_char(*c) => {
+            let msg = format!("keyword {:?} is followed by identifier character: {:?}", keyword, c);
+            let offset = parse_context.next_offset();
+            let pe = ParseError::of_mismatch(parse_context.original_input(), offset, length + 1, msg);
+            ParseResult::failed_with_uncommitted(parse_context, pe)
+          }
+          _ => ParseResult::successful(parse_context, value, length),
+        },
+        failure => failure,
+      }
+    });
+    self.lexeme(keyword_parser)
+  }
+}
+
+fn starts_with(input: &[char], s: &str) -> bool {
+  s.chars().enumerate().all(|(index, c)| input.get(index) == Some(&c))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_trivia() {
+    let lexer = Lexer::new()
+      .with_line_comment("#")
+      .with_block_comment("/*", "*/")
+      .with_nested_block_comment("(*", "*)");
+    let input = " # c\n /* a /* b */ (* (* *) *)x".chars().collect::<Vec<_>>();
+
+    let result = lexer.trivia().parse(&input);
+
+    assert_eq!(result.consumed_count(), input.len() - 1);
+  }
+
+  #[test]
+  fn test_unterminated_block_comment() {
+    let lexer = Lexer::new().with_nested_block_comment("/*", "*/");
+    let input = "a /* /* */".chars().collect::<Vec<_>>();
+
+    let result = lexer.symbol("a").parse(&input);
+
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Committed));
+    assert_eq!(result.failure().unwrap().input_string().unwrap(), "/* /* */");
+  }
+
+  #[test]
+  fn test_keyword_boundary() {
+    let lexer = Lexer::new().with_identifier_char(|c| c.is_ascii_alphanumeric() || c == '-');
+    let input = "end-if end".chars().collect::<Vec<_>>();
+    let parser = lexer.keyword("end").or(lexer.symbol("end-if"));
+
+    let result = parser.parse(&input);
+    assert_eq!(result.consumed_count(), 7);
+
+    let result = lexer.keyword("end").parse(&input[7..]);
+    assert_eq!(result.consumed_count(), 3);
+  }
+
+  #[test]
+  #[should_panic(expected = "must not be empty")]
+  fn test_empty_comment_delimiter() {
+    let _ = Lexer::new().with_block_comment("/*", "");
+  }
+
+  #[test]
+  #[should_panic(expected = "must not be empty")]
+  fn test_empty_line_comment() {
+    let _ = Lexer::new().with_line_comment("");
+  }
+}
@@ -0,0 +1,421 @@
+use crate::prelude::*;
+use std::num::{ParseFloatError, ParseIntError};
+use std::str::FromStr;
+
+/// An integer type that can be produced by the integer literal parsers.
+pub trait IntegerLiteral: Sized {
+  /// Whether the type accepts a leading sign.
+  const SIGNED: bool;
+
+  /// Converts a string of digits in the given radix.
+  fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError>;
+}
+
+macro_rules! impl_integer_literal {
+  ( $signed:expr; $($t:ty),+ ) => {
+    $(
+      impl IntegerLiteral for $t {
+        const SIGNED: bool = $signed;
+
+        fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError> {
+          <$t>::from_str_radix(s, radix)
+        }
+      }
+    )+
+  };
+}
+
+impl_integer_literal!(true; i8, i16, i32, i64, i128, isize);
+impl_integer_literal!(false; u8, u16, u32, u64, u128, usize);
+
+/// A floating point type that can be produced by the float literal parsers.
+pub trait FloatLiteral: FromStr<Err = ParseFloatError> {
+  /// Whether the value is neither infinite nor NaN.
+  fn is_finite_value(&self) -> bool;
+}
+
+impl FloatLiteral for f32 {
+  fn is_finite_value(&self) -> bool {
+    self.is_finite()
+  }
+}
+
+impl FloatLiteral for f64 {
+  fn is_finite_value(&self) -> bool {
+    self.is_finite()
+  }
+}
+
+/// Scans digits of `radix` starting at `index`, optionally allowing single underscores between digits.
+///
+/// Returns the index after the last digit, or `None` if no digit was found.
+fn scan_digits<I>(input: &[I], index: usize, radix: u32, underscores: bool, text: &mut String) -> Option<usize>
+where
+  I: Element + Clone, {
+  let mut index = index;
+  let mut found = false;
+  while let Some(c) = input.get(index).map(|e| e.clone().to_char()) {
+    if c.is_digit(radix) {
+      text.push(c);
+      found = true;
+      index += 1;
+    } else if c == '_'
+      && underscores
+      && found
+      && input
+        .get(index + 1)
+        .map(|e| e.clone().to_char().is_digit(radix))
+        .unwrap_or(false)
+    {
+      index += 1;
+    } else {
+      break;
+    }
+  }
+  found.then_some(index)
+}
+
+/// Scans an optional sign at `index`, returning the index after it.
+fn scan_sign<I>(input: &[I], index: usize, text: &mut String) -> usize
+where
+  I: Element + Clone, {
+  match input.get(index).map(|e| e.clone().to_char()) {
+    Some(c @ ('+' | '-')) => {
+      text.push(c);
+      index + 1
+    }
+    _ => index,
+  }
+}
+
+/// Scans a case-insensitive prefix at `index`, returning the index after it.
+fn scan_prefix<I>(input: &[I], index: usize, prefix: &str) -> Option<usize>
+where
+  I: Element + Clone, {
+  let mut index = index;
+  for p in prefix.chars() {
+    match input.get(index).map(|e| e.clone().to_char()) {
+      Some(c) if c.eq_ignore_ascii_case(&p) => index += 1,
+      _ => return None,
+    }
+  }
+  Some(index)
+}
+
+fn integer_literal<'a, I, T>(
+  name: &'static str,
+  prefix: Option<&'static str>,
+  radix: u32,
+  underscores: bool,
+) -> Parser<'a, I, T, impl ParserFn<'a, I, T>>
+where
+  I: Element + Clone + 'a,
+  T: IntegerLiteral + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, I>| {
+    let input = parse_context.input();
+    let offset = parse_context.next_offset();
+    let mut text = String::new();
+    let index = if T::SIGNED { scan_sign(input, 0, &mut text) } else { 0 };
+    let index = match prefix {
+      Some(prefix) => scan_prefix(input, index, prefix),
+      None => Some(index),
+    };
+    match index.and_then(|index| scan_digits(input, index, radix, underscores, &mut text)) {
+      Some(length) => match T::from_str_radix(&text, radix) {
+        Ok(value) => ParseResult::successful(parse_context, value, length),
+        Err(err) => {
+          let msg = format!("{} literal out of range: {}", name, err);
+          let pe = ParseError::of_conversion(parse_context.original_input(), offset, length, msg);
+          ParseResult::failed_with_uncommitted(parse_context, pe)
+        }
+      },
+      None if input.is_empty() => ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete()),
+      None => {
+        let msg = format!("expect {} literal, found: {:?}", name, input[0]);
+        let pe = ParseError::of_mismatch(parse_context.original_input(), offset, 1, msg);
+        ParseResult::failed_with_uncommitted(parse_context, pe)
+      }
+    }
+  })
+}
+
+fn float_literal<'a, I, T>(underscores: bool) -> Parser<'a, I, T, impl ParserFn<'a, I, T>>
+where
+  I: Element + Clone + 'a,
+  T: FloatLiteral + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, I>| {
+    let input = parse_context.input();
+    let offset = parse_context.next_offset();
+    let mut text = String::new();
+    let index = scan_sign(input, 0, &mut text);
+    let mut length = match scan_digits(input, index, 10, underscores, &mut text) {
+      Some(length) => length,
+      None if input.is_empty() => {
+        return ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete());
+      }
+      None => {
+        let msg = format!("expect float literal, found: {:?}", input[0]);
+        let pe = ParseError::of_mismatch(parse_context.original_input(), offset, 1, msg);
+        return ParseResult::failed_with_uncommitted(parse_context, pe);
+      }
+    };
+    if let Some(index) = scan_prefix(input, length, ".") {
+      let mut fraction = String::from(".");
+      if let Some(index) = scan_digits(input, index, 10, underscores, &mut fraction) {
+        text.push_str(&fraction);
+        length = index;
+      }
+    }
+    let exponent_index = scan_prefix(input, length, "e");
+    if let Some(index) = exponent_index {
+      let mut exponent = String::from("e");
+      let index = scan_sign(input, index, &mut exponent);
+      if let Some(index) = scan_digits(input, index, 10, underscores, &mut exponent) {
+        text.push_str(&exponent);
+        length = index;
+      }
+    }
+    match T::from_str(&text) {
+      Ok(value) if value.is_finite_value() => ParseResult::successful(parse_context, value, length),
+      Ok(_) => {
+        let msg = format!("float literal out of range: {}", text);
+        let pe = ParseError::of_conversion(parse_context.original_input(), offset, length, msg);
+        ParseResult::failed_with_uncommitted(parse_context, pe)
+      }
+      Err(err) => {
+        let msg = format!("invalid float literal: {}", err);
+        let pe = ParseError::of_conversion(parse_context.original_input(), offset, length, msg);
+        ParseResult::failed_with_uncommitted(parse_context, pe)
+      }
+    }
+  })
+}
+
+/// The input elements the numeric literal parsers read, `char` and `u8` among others.
+///
+/// The parsers are associated functions of the input type so that only the value type needs to be named, as in
+/// `char::integer::<i64>()` or `u8::float::<f64>()`.
+pub trait NumberInput: Element + Clone {
+  /// Returns a [Parser] that parses a decimal integer literal.
+  ///
+  /// Signed types accept a leading `+` or `-`. A value that does not fit in `T` is reported as
+  /// [ParseError::Conversion] covering the whole literal.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let input = b"-128,256";
+  ///
+  /// let parser = u8::integer::<i8>();
+  ///
+  /// let result = parser.parse(input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), -128);
+  ///
+  /// let result = u8::integer::<u8>().parse(&input[5..]);
+  ///
+  /// assert!(result.is_failure());
+  /// assert!(result.failure().unwrap().is_conversion());
+  /// ```
+  fn integer<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: IntegerLiteral + 'a, {
+    integer_literal("integer", None, 10, false)
+  }
+
+  /// Returns a [Parser] that parses a decimal integer literal whose digits may be separated by single underscores,
+  /// such as `1_000_000`.
+  fn integer_with_underscores<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: IntegerLiteral + 'a, {
+    integer_literal("integer", None, 10, true)
+  }
+
+  /// Returns a [Parser] that parses a hexadecimal integer literal prefixed with `0x`.
+  ///
+  /// Signed types accept a leading `+` or `-` before the prefix, as in `-0x1F`.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let input = "0xFF".chars().collect::<Vec<_>>();
+  ///
+  /// let parser = char::hex_integer::<u32>();
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), 255);
+  /// ```
+  fn hex_integer<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: IntegerLiteral + 'a, {
+    integer_literal("hex integer", Some("0x"), 16, false)
+  }
+
+  /// Returns a [Parser] that parses a hexadecimal integer literal prefixed with `0x`, allowing underscores.
+  fn hex_integer_with_underscores<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: IntegerLiteral + 'a, {
+    integer_literal("hex integer", Some("0x"), 16, true)
+  }
+
+  /// Returns a [Parser] that parses an octal integer literal prefixed with `0o`.
+  fn octal_integer<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: IntegerLiteral + 'a, {
+    integer_literal("octal integer", Some("0o"), 8, false)
+  }
+
+  /// Returns a [Parser] that parses an octal integer literal prefixed with `0o`, allowing underscores.
+  fn octal_integer_with_underscores<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: IntegerLiteral + 'a, {
+    integer_literal("octal integer", Some("0o"), 8, true)
+  }
+
+  /// Returns a [Parser] that parses a binary integer literal prefixed with `0b`.
+  fn binary_integer<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: IntegerLiteral + 'a, {
+    integer_literal("binary integer", Some("0b"), 2, false)
+  }
+
+  /// Returns a [Parser] that parses a binary integer literal prefixed with `0b`, allowing underscores.
+  fn binary_integer_with_underscores<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: IntegerLiteral + 'a, {
+    integer_literal("binary integer", Some("0b"), 2, true)
+  }
+
+  /// Returns a [Parser] that parses a decimal floating point literal such as `-1.5e10`.
+  ///
+  /// The fraction and the exponent are optional. A value that overflows to infinity is reported as
+  /// [ParseError::Conversion] covering the whole literal.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let input = "-1.25e2]".chars().collect::<Vec<_>>();
+  ///
+  /// let parser = char::float::<f64>();
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.consumed_count(), 7);
+  /// assert_eq!(result.success().unwrap(), -125.0);
+  /// ```
+  fn float<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: FloatLiteral + 'a, {
+    float_literal(false)
+  }
+
+  /// Returns a [Parser] that parses a decimal floating point literal whose digits may be separated by single
+  /// underscores.
+  fn float_with_underscores<'a, T>() -> Parser<'a, Self, T, impl ParserFn<'a, Self, T>>
+  where
+    Self: 'a,
+    T: FloatLiteral + 'a, {
+    float_literal(true)
+  }
+}
+
+impl<I: Element + Clone> NumberInput for I {}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_integer_overflow_span() {
+    let input = "x=70000".chars().collect::<Vec<_>>();
+    let parser = tag("x=") * char::integer::<u16>();
+
+    let error = parser.parse(&input).failure().unwrap();
+
+    assert!(error.is_conversion());
+    assert_eq!(error.input_string().unwrap(), "70000");
+  }
+
+  #[test]
+  fn test_integer_with_underscores() {
+    let input = b"1_000_ 2__0";
+
+    let result = u8::integer_with_underscores::<u32>().parse(input);
+    assert_eq!(result.consumed_count(), 5);
+    assert_eq!(result.success().unwrap(), 1000);
+
+    let result = u8::integer_with_underscores::<u32>().parse(&input[7..]);
+    assert_eq!(result.consumed_count(), 1);
+
+    let result = u8::integer::<u32>().parse(input);
+    assert_eq!(result.success().unwrap(), 1);
+  }
+
+  #[test]
+  fn test_prefixed_integers() {
+    assert_eq!(u8::binary_integer::<u8>().parse(b"0b1010").success(), Some(10));
+    assert_eq!(u8::octal_integer::<u8>().parse(b"0o17").success(), Some(15));
+    assert_eq!(
+      u8::hex_integer_with_underscores::<u32>()
+        .parse(b"0xdead_beef")
+        .success(),
+      Some(0xdead_beef)
+    );
+    assert!(u8::hex_integer::<u32>().parse(b"0x").is_failure());
+    assert!(u8::binary_integer::<u8>().parse(b"0b1_0").success() == Some(1));
+  }
+
+  #[test]
+  fn test_signed_prefixed_integers() {
+    assert_eq!(u8::hex_integer::<i32>().parse(b"-0x1F").success(), Some(-31));
+    assert_eq!(u8::octal_integer::<i8>().parse(b"+0o17").success(), Some(15));
+    assert_eq!(u8::binary_integer::<i8>().parse(b"-0b1000_0000").success(), Some(-8));
+    assert_eq!(
+      u8::binary_integer_with_underscores::<i8>()
+        .parse(b"-0b1000_0000")
+        .success(),
+      Some(-128)
+    );
+    assert!(u8::hex_integer::<u32>().parse(b"-0x1F").is_failure());
+  }
+
+  #[test]
+  fn test_float() {
+    let input = "1.5e+3 2. 3e 1e999".chars().collect::<Vec<_>>();
+
+    let result = char::float::<f64>().parse(&input);
+    assert_eq!(result.consumed_count(), 6);
+    assert_eq!(result.success().unwrap(), 1500.0);
+
+    let result = char::float::<f32>().parse(&input[7..]);
+    assert_eq!(result.consumed_count(), 1);
+
+    let result = char::float::<f32>().parse(&input[10..]);
+    assert_eq!(result.consumed_count(), 1);
+
+    let result = char::float::<f64>().parse(&input[13..]);
+    assert!(result.failure().unwrap().is_conversion());
+
+    let result = u8::float_with_underscores::<f64>().parse(b"1_000.000_1");
+    assert_eq!(result.success().unwrap(), 1000.0001);
+  }
+}
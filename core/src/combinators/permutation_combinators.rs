@@ -0,0 +1,363 @@
+use crate::prelude::*;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// A field of a permutation parser.
+///
+/// A field is either required (appears exactly once) or optional (appears at most once).
+pub trait PermutationField<'a, I: 'a> {
+  /// The value parsed by the field.
+  type Value;
+  /// The value reported in the result of the permutation parser.
+  type Output;
+
+  /// Runs the field parser.
+  fn run_field(&self, parse_context: ParseContext<'a, I>) -> ParseResult<'a, I, Self::Value>;
+
+  /// Converts the parsed value to the output, or returns `None` when a required field is missing.
+  fn finish(value: Option<Self::Value>) -> Option<Self::Output>;
+}
+
+/// A required field of a permutation parser. See [perm].
+#[derive(Clone)]
+pub struct PermRequired<P, A> {
+  parser: P,
+  _phantom: PhantomData<A>,
+}
+
+/// An optional field of a permutation parser. See [perm_opt].
+#[derive(Clone)]
+pub struct PermOptional<P, A> {
+  parser: P,
+  _phantom: PhantomData<A>,
+}
+
+/// Marks a parser as a required field of a permutation parser.
+///
+/// [Parser] values can also be used directly as required fields.
+pub fn perm<'a, I: 'a, A, P>(parser: P) -> PermRequired<P, A>
+where
+  P: ParserRunner<'a, I, A>, {
+  PermRequired {
+    parser,
+    _phantom: PhantomData,
+  }
+}
+
+/// Marks a parser as an optional field of a permutation parser.
+pub fn perm_opt<'a, I: 'a, A, P>(parser: P) -> PermOptional<P, A>
+where
+  P: ParserRunner<'a, I, A>, {
+  PermOptional {
+    parser,
+    _phantom: PhantomData,
+  }
+}
+
+impl<'a, I: 'a, A, P> PermutationField<'a, I> for PermRequired<P, A>
+where
+  P: ParserRunner<'a, I, A>,
+{
+  type Output = A;
+  type Value = A;
+
+  fn run_field(&self, parse_context: ParseContext<'a, I>) -> ParseResult<'a, I, A> {
+    self.parser.run(parse_context)
+  }
+
+  fn finish(value: Option<A>) -> Option<A> {
+    value
+  }
+}
+
+impl<'a, I: 'a, A, P> PermutationField<'a, I> for PermOptional<P, A>
+where
+  P: ParserRunner<'a, I, A>,
+{
+  type Output = Option<A>;
+  type Value = A;
+
+  fn run_field(&self, parse_context: ParseContext<'a, I>) -> ParseResult<'a, I, A> {
+    self.parser.run(parse_context)
+  }
+
+  fn finish(value: Option<A>) -> Option<Option<A>> {
+    Some(value)
+  }
+}
+
+impl<'a, I: 'a, A: 'a, F> PermutationField<'a, I> for Parser<'a, I, A, F>
+where
+  F: Fn(ParseContext<'a, I>) -> ParseResult<'a, I, A> + 'a,
+{
+  type Output = A;
+  type Value = A;
+
+  fn run_field(&self, parse_context: ParseContext<'a, I>) -> ParseResult<'a, I, A> {
+    self.run(parse_context)
+  }
+
+  fn finish(value: Option<A>) -> Option<A> {
+    value
+  }
+}
+
+/// A tuple of [PermutationField]s.
+pub trait PermutationFields<'a, I: 'a> {
+  /// The tuple of field outputs in declaration order.
+  type Output;
+
+  /// Parses the fields in any order, separated by `separator`.
+  fn run_permutation<P2, B>(
+    &self,
+    parse_context: ParseContext<'a, I>,
+    separator: &P2,
+  ) -> ParseResult<'a, I, Self::Output>
+  where
+    P2: ParserRunner<'a, I, B>;
+}
+
+macro_rules! impl_permutation_fields {
+  ( $( ($idx:tt, $field:ident, $slot:ident) ),+ ) => {
+    impl<'a, I: Debug + 'a, $($field),+> PermutationFields<'a, I> for ($($field,)+)
+    where
+      $($field: PermutationField<'a, I>),+
+    {
+      type Output = ($(<$field as PermutationField<'a, I>>::Output,)+);
+
+      fn run_permutation<P2, B>(
+        &self,
+        parse_context: ParseContext<'a, I>,
+        separator: &P2,
+      ) -> ParseResult<'a, I, Self::Output>
+      where
+        P2: ParserRunner<'a, I, B>, {
+        $(let mut $slot = None;)+
+        let mut all_length = 0;
+        let mut count = 0;
+
+        loop {
+          let mut sep_length = 0;
+          if count > 0 {
+            match separator.run(parse_context.add_offset(all_length)) {
+              ParseResult::Success { length, .. } => sep_length = length,
+              ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => break,
+              ParseResult::Failure {
+                parse_context,
+                error,
+                committed_status,
+              } => return ParseResult::failed(parse_context, error, committed_status),
+            }
+          }
+
+          let pc = parse_context.add_offset(all_length + sep_length);
+          let mut matched = false;
+          $(
+            if !matched && $slot.is_none() {
+              match self.$idx.run_field(pc.with_same_state()) {
+                ParseResult::Success { length: 0, .. } => {}
+                ParseResult::Success { value, length, .. } => {
+                  $slot = Some(value);
+                  all_length += sep_length + length;
+                  count += 1;
+                  matched = true;
+                }
+                ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => {}
+                ParseResult::Failure {
+                  parse_context,
+                  error,
+                  committed_status,
+                } => return ParseResult::failed(parse_context, error, committed_status),
+              }
+            }
+          )+
+          if !matched {
+            // Only a field that is already filled matches here.
+            $(
+              if $slot.is_some() {
+                if let ParseResult::Success { length: 1.., .. } = self.$idx.run_field(pc.with_same_state()) {
+                  let pe = ParseError::of_permutation(pc.next_offset(), $idx, PermutationErrorKind::Duplicate);
+                  return ParseResult::failed_with_commit(parse_context, pe);
+                }
+              }
+            )+
+            break;
+          }
+        }
+
+        let offset = parse_context.next_offset() + all_length;
+        $(
+          let $slot = match <$field as PermutationField<'a, I>>::finish($slot) {
+            Some(value) => value,
+            None => {
+              let pe = ParseError::of_permutation(offset, $idx, PermutationErrorKind::Missing);
+              return ParseResult::failed(parse_context, pe, (all_length != 0).into());
+            }
+          };
+        )+
+        ParseResult::successful(parse_context, ($($slot,)+), all_length)
+      }
+    }
+  };
+}
+
+impl_permutation_fields!((0, F0, v0));
+impl_permutation_fields!((0, F0, v0), (1, F1, v1));
+impl_permutation_fields!((0, F0, v0), (1, F1, v1), (2, F2, v2));
+impl_permutation_fields!((0, F0, v0), (1, F1, v1), (2, F2, v2), (3, F3, v3));
+impl_permutation_fields!((0, F0, v0), (1, F1, v1), (2, F2, v2), (3, F3, v3), (4, F4, v4));
+impl_permutation_fields!(
+  (0, F0, v0),
+  (1, F1, v1),
+  (2, F2, v2),
+  (3, F3, v3),
+  (4, F4, v4),
+  (5, F5, v5)
+);
+impl_permutation_fields!(
+  (0, F0, v0),
+  (1, F1, v1),
+  (2, F2, v2),
+  (3, F3, v3),
+  (4, F4, v4),
+  (5, F5, v5),
+  (6, F6, v6)
+);
+impl_permutation_fields!(
+  (0, F0, v0),
+  (1, F1, v1),
+  (2, F2, v2),
+  (3, F3, v3),
+  (4, F4, v4),
+  (5, F5, v5),
+  (6, F6, v6),
+  (7, F7, v7)
+);
+
+/// Returns a [Parser] that parses the fields of a tuple in any order.
+///
+/// Each field must appear at most once, and required fields exactly once. Values are returned in declaration order.
+/// A missing required field or a duplicated field is reported as [ParseError::Permutation].
+/// Fields that succeed without consuming input are ignored.
+///
+/// - fields: a tuple of fields, see [perm] and [perm_opt]
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text: &str = "cab";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = permutation((elm('a'), perm_opt(elm('b')), elm('c'), perm_opt(elm('d'))));
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), ('a', Some('b'), 'c', None));
+/// ```
+pub fn permutation<'a, I, T>(fields: T) -> Parser<'a, I, T::Output, impl ParserFn<'a, I, T::Output>>
+where
+  I: 'a,
+  T: PermutationFields<'a, I> + 'a, {
+  permutation_sep(fields, unit())
+}
+
+/// Returns a [Parser] that parses the fields of a tuple in any order, separated by `separator`.
+///
+/// - fields: a tuple of fields, see [perm] and [perm_opt]
+/// - separator: a separator parser
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = "port=80;host=a".chars().collect::<Vec<_>>();
+/// let duplicated = "port=80;port=81".chars().collect::<Vec<_>>();
+///
+/// let host = tag("host=") * elm_alpha_ref().of_many1().map(|cs| cs.len());
+/// let port = tag("port=") * elm_digit_ref().of_many1().map(|cs| cs.len());
+/// let parser = permutation_sep((host, perm_opt(port)), elm(';'));
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), (1, Some(2)));
+///
+/// let error = parser.parse(&duplicated).failure().unwrap();
+///
+/// assert_eq!(error, ParseError::of_permutation(8, 1, PermutationErrorKind::Duplicate));
+/// ```
+pub fn permutation_sep<'a, I, T, B, P2>(
+  fields: T,
+  separator: P2,
+) -> Parser<'a, I, T::Output, impl ParserFn<'a, I, T::Output>>
+where
+  I: 'a,
+  T: PermutationFields<'a, I> + 'a,
+  P2: ParserRunner<'a, I, B> + 'a, {
+  Parser::new(move |parse_context| fields.run_permutation(parse_context, &separator))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_permutation_missing_required() {
+    let text = "ba";
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = permutation((elm('a'), elm('b'), perm(elm('c'))));
+
+    let result = parser.parse(&input);
+
+    assert!(result.is_failure());
+    assert_eq!(
+      result.failure().unwrap(),
+      ParseError::of_permutation(2, 2, PermutationErrorKind::Missing)
+    );
+  }
+
+  #[test]
+  fn test_permutation_duplicate() {
+    let text = "aba";
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = permutation((elm('a'), elm('b')));
+
+    let result = parser.parse(&input);
+
+    assert!(result.is_failure());
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Committed));
+    assert_eq!(
+      result.failure().unwrap(),
+      ParseError::of_permutation(2, 0, PermutationErrorKind::Duplicate)
+    );
+  }
+
+  #[test]
+  fn test_permutation_filled_field_falls_through() {
+    let text = "ab";
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = permutation((elm_alpha_ref().map(|c| *c), elm('b')));
+
+    let result = parser.parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.success().unwrap(), ('a', 'b'));
+  }
+
+  #[test]
+  fn test_permutation_sep_leaves_trailing_separator() {
+    let text = "b,a,";
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = permutation_sep((elm('a'), perm_opt(elm('b'))), elm(','));
+
+    let result = parser.parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 3);
+    assert_eq!(result.success().unwrap(), ('a', Some('b')));
+  }
+}
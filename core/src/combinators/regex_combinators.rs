@@ -0,0 +1,442 @@
+use crate::prelude::*;
+use regex::bytes::Regex as BytesRegex;
+use regex::Regex;
+use regex_automata::hybrid::dfa::{Cache, DFA};
+use regex_automata::nfa::thompson;
+use regex_automata::util::start;
+use regex_automata::Anchored;
+use regex_syntax::hir::{Capture, Hir, HirKind, Repetition};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A [Parser] of byte input returning the matched bytes.
+type BytesParser<'a, F> = Parser<'a, u8, &'a [u8], F>;
+
+/// A regular expression matched against `char` input without copying the remaining input.
+///
+/// The match runs on a lazy DFA fed with the UTF-8 encoding of each element, and stops as soon as no longer match
+/// is possible. If the DFA gives up (e.g. on a Unicode word boundary after non-ASCII text), the matcher falls back to
+/// [Regex] on a window of the remaining input, which ends where the pattern without look-around assertions can no
+/// longer match.
+pub(crate) struct CharRegex {
+  dfa: DFA,
+  cache: RefCell<Cache>,
+  window_dfa: DFA,
+  window_cache: RefCell<Cache>,
+  regex: Regex,
+}
+
+impl CharRegex {
+  pub(crate) fn new(pattern: &str) -> Result<Self, regex::Error> {
+    let regex = Regex::new(&format!("^(?:{})", pattern))?;
+    let syntax_error = |e: &dyn std::error::Error| regex::Error::Syntax(e.to_string());
+    let dfa = DFA::builder()
+      .configure(DFA::config().unicode_word_boundary(true))
+      .build(pattern)
+      .map_err(|e| syntax_error(&e))?;
+    let hir = regex_syntax::parse(pattern).map_err(|e| syntax_error(&e))?;
+    let nfa = thompson::Compiler::new()
+      .build_from_hir(&without_look_around(&hir))
+      .map_err(|e| syntax_error(&e))?;
+    let window_dfa = DFA::builder().build_from_nfa(nfa).map_err(|e| syntax_error(&e))?;
+    let cache = RefCell::new(dfa.create_cache());
+    let window_cache = RefCell::new(window_dfa.create_cache());
+    Ok(Self {
+      dfa,
+      cache,
+      window_dfa,
+      window_cache,
+      regex,
+    })
+  }
+
+  fn as_str(&self) -> &str {
+    self.regex.as_str()
+  }
+
+  /// Returns the number of elements matched at the start of `input`.
+  pub(crate) fn find_len(&self, input: &[char]) -> Option<usize> {
+    match self.find_len_in_place(input) {
+      Ok(length) => length,
+      Err(()) => {
+        let text = self.match_window(input);
+        self.regex.find(&text).map(|m| text[..m.end()].chars().count())
+      }
+    }
+  }
+
+  /// Returns the capture groups of the match at the start of `input`, with spans in elements from `offset`.
+  fn captures(&self, input: &[char], offset: usize) -> Option<(CaptureGroups, usize)> {
+    let text = self.match_window(input);
+    let captures = self.regex.captures(&text)?;
+    let groups = captures
+      .iter()
+      .map(|m| {
+        m.map(|m| {
+          let start = offset + text[..m.start()].chars().count();
+          let end = start + m.as_str().chars().count();
+          (m.as_str().to_string(), start..end)
+        })
+      })
+      .collect();
+    Some((groups, text[..captures.get_match().end()].chars().count()))
+  }
+
+  fn find_len_in_place(&self, input: &[char]) -> Result<Option<usize>, ()> {
+    let mut cache = self.cache.borrow_mut();
+    let config = start::Config::new().anchored(Anchored::Yes);
+    let mut state = self.dfa.start_state(&mut cache, &config).map_err(|_| ())?;
+    let mut last_match = None;
+    let mut buf = [0; 4];
+    for (index, c) in input.iter().enumerate() {
+      for (byte_index, byte) in c.encode_utf8(&mut buf).bytes().enumerate() {
+        state = self.dfa.next_state(&mut cache, state, byte).map_err(|_| ())?;
+        if state.is_tagged() {
+          if state.is_quit() {
+            return Err(());
+          }
+          // Matches are delayed by one byte, so a match state here means the match ends before this element.
+          if state.is_match() && byte_index == 0 {
+            last_match = Some(index);
+          }
+          if state.is_dead() {
+            return Ok(last_match);
+          }
+        }
+      }
+    }
+    state = self.dfa.next_eoi_state(&mut cache, state).map_err(|_| ())?;
+    if state.is_match() {
+      last_match = Some(input.len());
+    }
+    Ok(last_match)
+  }
+
+  /// Returns the text of the shortest prefix of `input` on which [Regex] finds the same match as on all of `input`.
+  ///
+  /// Assertions only restrict a match, so no match ends after the element on which `window_dfa` dies. That element
+  /// is kept for the assertions looking at it.
+  fn match_window(&self, input: &[char]) -> String {
+    let mut cache = self.window_cache.borrow_mut();
+    let config = start::Config::new().anchored(Anchored::Yes);
+    let Ok(mut state) = self.window_dfa.start_state(&mut cache, &config) else {
+      return String::from_iter(input);
+    };
+    let mut buf = [0; 4];
+    for (index, c) in input.iter().enumerate() {
+      for byte in c.encode_utf8(&mut buf).bytes() {
+        match self.window_dfa.next_state(&mut cache, state, byte) {
+          Ok(next) if next.is_dead() => return String::from_iter(&input[..=index]),
+          Ok(next) => state = next,
+          Err(_) => return String::from_iter(input),
+        }
+      }
+    }
+    String::from_iter(input)
+  }
+}
+
+/// Returns `hir` with every look-around assertion replaced by the empty regex, so that it matches a superset of `hir`.
+fn without_look_around(hir: &Hir) -> Hir {
+  match hir.kind() {
+    HirKind::Look(_) => Hir::empty(),
+    HirKind::Repetition(repetition) => Hir::repetition(Repetition {
+      sub: Box::new(without_look_around(&repetition.sub)),
+      ..repetition.clone()
+    }),
+    HirKind::Capture(capture) => Hir::capture(Capture {
+      sub: Box::new(without_look_around(&capture.sub)),
+      ..capture.clone()
+    }),
+    HirKind::Concat(hirs) => Hir::concat(hirs.iter().map(without_look_around).collect()),
+    HirKind::Alternation(hirs) => Hir::alternation(hirs.iter().map(without_look_around).collect()),
+    _ => hir.clone(),
+  }
+}
+
+/// The text and span of each group, `None` for groups that did not participate in the match.
+type CaptureGroups = Vec<Option<(String, Range<usize>)>>;
+
+/// The groups captured by [regex_captures].
+///
+/// Group `0` is the whole match. Spans are offsets of elements in the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexCaptures {
+  groups: CaptureGroups,
+  names: HashMap<String, usize>,
+}
+
+impl RegexCaptures {
+  /// Returns the text of the group at `index`, or `None` if the group did not participate in the match.
+  pub fn get(&self, index: usize) -> Option<&str> {
+    self.groups.get(index)?.as_ref().map(|(text, _)| text.as_str())
+  }
+
+  /// Returns the text of the named group `name`.
+  pub fn name(&self, name: &str) -> Option<&str> {
+    self.get(*self.names.get(name)?)
+  }
+
+  /// Returns the span of the group at `index`.
+  pub fn span(&self, index: usize) -> Option<Range<usize>> {
+    self.groups.get(index)?.as_ref().map(|(_, span)| span.clone())
+  }
+
+  /// Returns the span of the named group `name`.
+  pub fn name_span(&self, name: &str) -> Option<Range<usize>> {
+    self.span(*self.names.get(name)?)
+  }
+
+  /// Returns the text of the whole match.
+  pub fn as_str(&self) -> &str {
+    self.get(0).unwrap_or_default()
+  }
+
+  /// Returns the number of groups, including group `0`.
+  pub fn len(&self) -> usize {
+    self.groups.len()
+  }
+
+  /// Returns `true` if there are no groups.
+  pub fn is_empty(&self) -> bool {
+    self.groups.is_empty()
+  }
+}
+
+fn regex_failure<'a, A>(parse_context: ParseContext<'a, char>, pattern: &str) -> ParseResult<'a, char, A> {
+  let input = parse_context.input();
+  if input.is_empty() {
+    ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete())
+  } else {
+    let msg = format!("regex {:?} did not match, found: {:?}", pattern, input[0]);
+    let offset = parse_context.next_offset();
+    let pe = ParseError::of_mismatch(parse_context.original_input(), offset, 0, msg);
+    ParseResult::failed_with_uncommitted(parse_context, pe)
+  }
+}
+
+/// Returns a [ClonableParser] that parses a string that match a regular expression.
+///
+/// The pattern is anchored at the current position and matched without copying the remaining input. The consumed
+/// length is counted in elements, so non-ASCII text advances the input correctly.
+///
+/// - pattern: a regular expression
+///
+/// # Errors
+///
+/// Returns an error if `pattern` is not a valid regular expression.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text: &str = "abcdef";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = regex("[abc]+").unwrap();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), "abc");
+///
+/// assert!(regex("[abc").is_err());
+/// ```
+pub fn regex<'a>(pattern: &str) -> Result<Parser<'a, char, String, impl ParserFn<'a, char, String>>, regex::Error> {
+  let regex = CharRegex::new(pattern)?;
+  Ok(Parser::new(move |parse_context: ParseContext<'a, char>| {
+    let input = parse_context.input();
+    match regex.find_len(input) {
+      Some(length) => ParseResult::successful(parse_context, String::from_iter(&input[..length]), length),
+      None => regex_failure(parse_context, regex.as_str()),
+    }
+  }))
+}
+
+/// Returns a [ClonableParser] that parses a string that match a regular expression, and returns its capture groups.
+///
+/// - pattern: a regular expression
+///
+/// # Errors
+///
+/// Returns an error if `pattern` is not a valid regular expression.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text: &str = "2024-05-06T";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = regex_captures(r"(?<year>\d{4})-(\d{2})-(\d{2})").unwrap();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// let captures = result.success().unwrap();
+/// assert_eq!(captures.as_str(), "2024-05-06");
+/// assert_eq!(captures.name("year"), Some("2024"));
+/// assert_eq!(captures.get(3), Some("06"));
+/// assert_eq!(captures.span(2), Some(5..7));
+/// ```
+pub fn regex_captures<'a>(
+  pattern: &str,
+) -> Result<Parser<'a, char, RegexCaptures, impl ParserFn<'a, char, RegexCaptures>>, regex::Error> {
+  let regex = CharRegex::new(pattern)?;
+  let names = regex
+    .regex
+    .capture_names()
+    .enumerate()
+    .filter_map(|(index, name)| name.map(|name| (name.to_string(), index)))
+    .collect::<HashMap<_, _>>();
+  Ok(Parser::new(move |parse_context: ParseContext<'a, char>| {
+    match regex.captures(parse_context.input(), parse_context.next_offset()) {
+      Some((groups, length)) => {
+        let captures = RegexCaptures {
+          groups,
+          names: names.clone(),
+        };
+        ParseResult::successful(parse_context, captures, length)
+      }
+      None => regex_failure(parse_context, regex.as_str()),
+    }
+  }))
+}
+
+/// Returns a [ClonableParser] that parses bytes matching a regular expression.
+///
+/// The pattern is matched directly against the remaining input with [regex::bytes::Regex], anchored at the current
+/// position, so it is safe to use on binary data.
+///
+/// - pattern: a regular expression
+///
+/// # Errors
+///
+/// Returns an error if `pattern` is not a valid regular expression.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let input = b"$5\r\nhello";
+///
+/// let parser = regex_bytes(r"\$[0-9]+").unwrap();
+///
+/// let result = parser.parse(input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), b"$5");
+/// ```
+pub fn regex_bytes<'a>(pattern: &str) -> Result<BytesParser<'a, impl ParserFn<'a, u8, &'a [u8]>>, regex::Error> {
+  let regex = BytesRegex::new(&format!("^(?:{})", pattern))?;
+  Ok(Parser::new(move |parse_context: ParseContext<'a, u8>| {
+    let input = parse_context.input();
+    match regex.find(input) {
+      Some(m) => ParseResult::successful(parse_context, m.as_bytes(), m.end()),
+      None if input.is_empty() => ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete()),
+      None => {
+        let msg = format!("regex {:?} did not match", regex.as_str());
+        let offset = parse_context.next_offset();
+        let pe = ParseError::of_mismatch(parse_context.original_input(), offset, 0, msg);
+        ParseResult::failed_with_uncommitted(parse_context, pe)
+      }
+    }
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_regex_non_ascii() {
+    let input = "日本語abc!".chars().collect::<Vec<_>>();
+    let parser = regex(r"\w+").unwrap() + elm_ref('!');
+
+    let result = parser.parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 7);
+    assert_eq!(result.success().unwrap().0, "日本語abc");
+  }
+
+  #[test]
+  fn test_regex_alternation_is_anchored() {
+    let input = "xab".chars().collect::<Vec<_>>();
+
+    assert!(regex("a|b").unwrap().parse(&input).is_failure());
+    assert_eq!(regex("x|xa").unwrap().parse(&input).success().unwrap(), "x");
+    assert_eq!(regex(r"x\b|xa").unwrap().parse(&input).success().unwrap(), "xa");
+    assert_eq!(regex("").unwrap().parse(&input).consumed_count(), 0);
+    assert!(regex("a")
+      .unwrap()
+      .parse(&input[3..])
+      .failure()
+      .unwrap()
+      .is_in_complete());
+  }
+
+  #[test]
+  fn test_regex_word_boundary_after_non_ascii() {
+    let input = "αβ γ".chars().collect::<Vec<_>>();
+
+    let result = regex(r"\w+\b").unwrap().parse(&input);
+
+    assert_eq!(result.consumed_count(), 2);
+  }
+
+  #[test]
+  fn test_regex_captures_spans() {
+    let input = "ключ=значение".chars().collect::<Vec<_>>();
+    let parser = elm_ref('к') * regex_captures(r"(?<key>\w+)=(?<value>\w+)?").unwrap();
+
+    let captures = parser.parse(&input).success().unwrap();
+
+    assert_eq!(captures.len(), 3);
+    assert_eq!(captures.name("key"), Some("люч"));
+    assert_eq!(captures.name_span("key"), Some(1..4));
+    assert_eq!(captures.name_span("value"), Some(5..13));
+    assert_eq!(captures.name("missing"), None);
+  }
+
+  #[test]
+  fn test_regex_captures_look_around_after_match() {
+    let input = "ab".chars().collect::<Vec<_>>();
+
+    let captures = regex_captures(r"(a)\B").unwrap().parse(&input).success().unwrap();
+    assert_eq!(captures.as_str(), "a");
+    assert_eq!(captures.span(1), Some(0..1));
+
+    assert!(regex_captures(r"a\b").unwrap().parse(&input).is_failure());
+  }
+
+  #[test]
+  fn test_regex_fallback_window() {
+    let input = "éé b éé".chars().collect::<Vec<_>>();
+
+    let result = regex(r"\w+\b|\w+ b").unwrap().parse(&input);
+    assert_eq!(result.consumed_count(), 2);
+
+    let result = regex(r"\w+ b\b").unwrap().parse(&input);
+    assert_eq!(result.consumed_count(), 4);
+    assert!(regex(r"\w+ b\B").unwrap().parse(&input).is_failure());
+  }
+
+  #[test]
+  fn test_regex_bytes() {
+    let input = b"\xffab\r\n";
+
+    let result = regex_bytes(r"(?-u)\xff[a-z]+").unwrap().parse(input);
+    assert_eq!(result.consumed_count(), 3);
+
+    let result = regex_bytes(r"a|\r\n").unwrap().parse(&input[2..]);
+    assert!(result.is_failure());
+
+    let result = (tag_bytes(b"\xffab") * regex_bytes(r"x|\r\n").unwrap()).parse(input);
+    assert_eq!(result.success().unwrap(), b"\r\n");
+  }
+}
@@ -0,0 +1,408 @@
+use crate::prelude::*;
+
+/// A table describing which escape sequences an [escaped_string] accepts.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let table = EscapeTable::new().with_escape('n', '\n').with_escape('\\', '\\').with_hex_escape(true);
+///
+/// assert_eq!(table.lookup('n'), Some('\n'));
+/// assert_eq!(table.lookup('t'), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EscapeTable {
+  escapes: Vec<(char, char)>,
+  unicode_escape: bool,
+  braced_unicode_escape: bool,
+  hex_escape: bool,
+  octal_escape: bool,
+}
+
+impl EscapeTable {
+  /// Create an empty table that accepts no escape sequences.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Create a table for JSON strings (RFC 8259).
+  ///
+  /// Accepts `\" \\ \/ \b \f \n \r \t` and `\uXXXX` with surrogate pairs.
+  pub fn json() -> Self {
+    Self::new()
+      .with_escape('"', '"')
+      .with_escape('\\', '\\')
+      .with_escape('/', '/')
+      .with_escape('b', '\x08')
+      .with_escape('f', '\x0C')
+      .with_escape('n', '\n')
+      .with_escape('r', '\r')
+      .with_escape('t', '\t')
+      .with_unicode_escape(true)
+  }
+
+  /// Create a table for Rust string literals.
+  ///
+  /// Accepts `\" \' \\ \0 \n \r \t`, `\xNN` and `\u{...}`.
+  pub fn rust() -> Self {
+    Self::new()
+      .with_escape('"', '"')
+      .with_escape('\'', '\'')
+      .with_escape('\\', '\\')
+      .with_escape('0', '\0')
+      .with_escape('n', '\n')
+      .with_escape('r', '\r')
+      .with_escape('t', '\t')
+      .with_hex_escape(true)
+      .with_braced_unicode_escape(true)
+  }
+
+  /// Create a table for C string literals.
+  ///
+  /// Accepts `\" \' \? \\ \a \b \f \n \r \t \v`, `\xNN` and octal escapes of up to three digits.
+  pub fn c() -> Self {
+    Self::new()
+      .with_escape('"', '"')
+      .with_escape('\'', '\'')
+      .with_escape('?', '?')
+      .with_escape('\\', '\\')
+      .with_escape('a', '\x07')
+      .with_escape('b', '\x08')
+      .with_escape('f', '\x0C')
+      .with_escape('n', '\n')
+      .with_escape('r', '\r')
+      .with_escape('t', '\t')
+      .with_escape('v', '\x0B')
+      .with_hex_escape(true)
+      .with_octal_escape(true)
+  }
+
+  /// Add a single character escape, replacing any existing mapping of `escape`.
+  pub fn with_escape(mut self, escape: char, value: char) -> Self {
+    self.escapes.retain(|(e, _)| *e != escape);
+    self.escapes.push((escape, value));
+    self
+  }
+
+  /// Enable or disable `\uXXXX` escapes. UTF-16 surrogate pairs must be written as two consecutive escapes.
+  pub fn with_unicode_escape(mut self, enabled: bool) -> Self {
+    self.unicode_escape = enabled;
+    self
+  }
+
+  /// Enable or disable `\u{...}` escapes with one to six hex digits.
+  pub fn with_braced_unicode_escape(mut self, enabled: bool) -> Self {
+    self.braced_unicode_escape = enabled;
+    self
+  }
+
+  /// Enable or disable `\xNN` escapes.
+  pub fn with_hex_escape(mut self, enabled: bool) -> Self {
+    self.hex_escape = enabled;
+    self
+  }
+
+  /// Enable or disable octal escapes (`\0` to `\377`).
+  pub fn with_octal_escape(mut self, enabled: bool) -> Self {
+    self.octal_escape = enabled;
+    self
+  }
+
+  /// Get the value of a single character escape.
+  pub fn lookup(&self, escape: char) -> Option<char> {
+    self.escapes.iter().find(|(e, _)| *e == escape).map(|(_, v)| *v)
+  }
+}
+
+/// The outcome of decoding one escape sequence: the decoded char and the number of elements after the escape char.
+type EscapeOutcome = Result<(char, usize), (usize, String, bool)>;
+
+fn hex_value(input: &[char], start: usize, len: usize) -> Option<u32> {
+  let digits = input.get(start..start + len)?;
+  digits
+    .iter()
+    .try_fold(0u32, |acc, c| c.to_digit(16).map(|d| acc * 16 + d))
+}
+
+/// Decodes the escape sequence following the escape char at `input[0]`.
+///
+/// On error, returns the length of the offending sequence, a message and whether the error is a conversion error.
+fn decode_escape(input: &[char], escape_char: char, table: &EscapeTable) -> EscapeOutcome {
+  let c = match input.get(1) {
+    Some(c) => *c,
+    None => return Err((1, "unterminated escape sequence".to_string(), false)),
+  };
+  if let Some(value) = table.lookup(c) {
+    return Ok((value, 2));
+  }
+  match c {
+    'u' if table.braced_unicode_escape && input.get(2) == Some(&'{') => {
+      let digits = input[3..].iter().take_while(|c| c.is_ascii_hexdigit()).count();
+      if digits == 0 || digits > 6 || input.get(3 + digits) != Some(&'}') {
+        return Err((
+          3 + digits,
+          "invalid unicode escape, expect \\u{1-6 hex digits}".to_string(),
+          false,
+        ));
+      }
+      let value = hex_value(input, 3, digits).unwrap_or_default();
+      match char::from_u32(value) {
+        Some(value) => Ok((value, 4 + digits)),
+        None => Err((4 + digits, format!("invalid unicode code point: {:X}", value), true)),
+      }
+    }
+    'u' if table.unicode_escape => {
+      let high = match hex_value(input, 2, 4) {
+        Some(value) => value,
+        None => return Err((2, "invalid unicode escape, expect \\uXXXX".to_string(), false)),
+      };
+      match high {
+        0xD800..=0xDBFF => {
+          let low = if input.get(6) == Some(&escape_char) && input.get(7) == Some(&'u') {
+            hex_value(input, 8, 4)
+          } else {
+            None
+          };
+          match low {
+            Some(low @ 0xDC00..=0xDFFF) => {
+              let value = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+              Ok((char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER), 12))
+            }
+            _ => Err((6, format!("unpaired high surrogate: {:X}", high), true)),
+          }
+        }
+        0xDC00..=0xDFFF => Err((6, format!("unpaired low surrogate: {:X}", high), true)),
+        _ => Ok((char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER), 6)),
+      }
+    }
+    'x' if table.hex_escape => match hex_value(input, 2, 2) {
+      Some(value) => Ok((char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER), 4)),
+      None => Err((2, "invalid hex escape, expect \\xNN".to_string(), false)),
+    },
+    '0'..='7' if table.octal_escape => {
+      let digits = input[1..].iter().take(3).take_while(|c| matches!(c, '0'..='7')).count();
+      let value = input[1..1 + digits]
+        .iter()
+        .fold(0u32, |acc, c| acc * 8 + c.to_digit(8).unwrap_or_default());
+      if value > 0o377 {
+        return Err((1 + digits, format!("octal escape out of range: {:o}", value), true));
+      }
+      Ok((char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER), 1 + digits))
+    }
+    _ => Err((2, format!("invalid escape sequence: {}{}", escape_char, c), false)),
+  }
+}
+
+/// Returns a [Parser] that parses a quoted string literal, decoding escape sequences according to `table`.
+///
+/// Once the opening quote has been read, errors are committed. Invalid escape sequences are reported as
+/// [ParseError::Mismatch] and invalid code points (such as unpaired surrogates) as [ParseError::Conversion], both
+/// covering the offending escape sequence.
+///
+/// - quote: the quote character
+/// - escape_char: the escape character, usually `\`
+/// - table: the accepted escape sequences
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text = r#""a\tb\u00e9\uD83D\uDE00""#;
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = escaped_string('"', '\\', EscapeTable::json());
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), "a\tbé😀");
+/// ```
+pub fn escaped_string<'a>(
+  quote: char,
+  escape_char: char,
+  table: EscapeTable,
+) -> Parser<'a, char, String, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, String> + 'a> {
+  Parser::new(move |parse_context: ParseContext<'a, char>| {
+    let input = parse_context.input();
+    let offset = parse_context.next_offset();
+    match input.first() {
+      Some(c) if *c == quote => {}
+      Some(c) => {
+        let msg = format!("expect string literal starting with {:?}, found: {:?}", quote, c);
+        let pe = ParseError::of_mismatch(parse_context.original_input(), offset, 1, msg);
+        return ParseResult::failed_with_uncommitted(parse_context, pe);
+      }
+      None => return ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete()),
+    }
+    let mut value = String::new();
+    let mut index = 1;
+    loop {
+      match input.get(index) {
+        Some(c) if *c == quote => return ParseResult::successful(parse_context, value, index + 1),
+        Some(c) if *c == escape_char => match decode_escape(&input[index..], escape_char, &table) {
+          Ok((c, length)) => {
+            value.push(c);
+            index += length;
+          }
+          Err((length, msg, is_conversion)) => {
+            let length = length.min(input.len() - index);
+            let pe = if is_conversion {
+              ParseError::of_conversion(parse_context.original_input(), offset + index, length, msg)
+            } else {
+              ParseError::of_mismatch(parse_context.original_input(), offset + index, length, msg)
+            };
+            return ParseResult::failed_with_commit(parse_context, pe);
+          }
+        },
+        Some(c) => {
+          value.push(*c);
+          index += 1;
+        }
+        None => {
+          let msg = format!("unterminated string literal, expect: {:?}", quote);
+          let pe = ParseError::of_mismatch(parse_context.original_input(), offset, index, msg);
+          return ParseResult::failed_with_commit(parse_context, pe);
+        }
+      }
+    }
+  })
+}
+
+/// Returns a [Parser] that parses a Rust style raw string literal such as `r"..."` or `r#"..."#`.
+///
+/// The content is returned as is, without decoding any escape sequences.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text = r###"r#"a "quoted" \n"#"###;
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = raw_string();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), r#"a "quoted" \n"#);
+/// ```
+pub fn raw_string<'a>(
+) -> Parser<'a, char, String, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, String> + 'a> {
+  Parser::new(move |parse_context: ParseContext<'a, char>| {
+    let input = parse_context.input();
+    let offset = parse_context.next_offset();
+    let hashes = input.iter().skip(1).take_while(|c| **c == '#').count();
+    if input.first() != Some(&'r') || input.get(1 + hashes) != Some(&'"') {
+      if input.is_empty() {
+        return ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete());
+      }
+      let msg = "expect raw string literal starting with r\" or r#\"".to_string();
+      let pe = ParseError::of_mismatch(
+        parse_context.original_input(),
+        offset,
+        (2 + hashes).min(input.len()),
+        msg,
+      );
+      return ParseResult::failed_with_uncommitted(parse_context, pe);
+    }
+    let start = 2 + hashes;
+    let end = (start..input.len())
+      .find(|i| input[*i] == '"' && input[i + 1..].iter().take(hashes).filter(|c| **c == '#').count() == hashes);
+    match end {
+      Some(end) => {
+        let value = input[start..end].iter().collect::<String>();
+        ParseResult::successful(parse_context, value, end + 1 + hashes)
+      }
+      None => {
+        let msg = format!("unterminated raw string literal, expect: \"{}", "#".repeat(hashes));
+        let pe = ParseError::of_mismatch(parse_context.original_input(), offset, input.len(), msg);
+        ParseResult::failed_with_commit(parse_context, pe)
+      }
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  fn parse(text: &str, table: EscapeTable) -> Result<String, (bool, usize, String)> {
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = escaped_string('"', '\\', table);
+    let result = parser.parse(&input).to_result().map_err(|e| {
+      (
+        e.is_conversion(),
+        e.input().map(|i| i.len()).unwrap_or(0),
+        e.input_string().unwrap_or_default(),
+      )
+    });
+    result
+  }
+
+  #[test]
+  fn test_escaped_string_tables() {
+    assert_eq!(
+      parse(r#""\x41\u{1F600}\'""#, EscapeTable::rust()),
+      Ok("A😀'".to_string())
+    );
+    assert_eq!(parse(r#""\101\0\7\?""#, EscapeTable::c()), Ok("A\0\x07?".to_string()));
+    assert_eq!(parse(r#""a\"b""#, EscapeTable::json()), Ok("a\"b".to_string()));
+  }
+
+  #[test]
+  fn test_escaped_string_errors() {
+    assert_eq!(
+      parse(r#""ab\qc""#, EscapeTable::json()),
+      Err((false, 2, "\\q".to_string()))
+    );
+    assert_eq!(
+      parse(r#""\uD83Dx""#, EscapeTable::json()),
+      Err((true, 6, "\\uD83D".to_string()))
+    );
+    assert_eq!(
+      parse(r#""\uDE00""#, EscapeTable::json()),
+      Err((true, 6, "\\uDE00".to_string()))
+    );
+    assert_eq!(
+      parse(r#""\u{110000}""#, EscapeTable::rust()),
+      Err((true, 10, "\\u{110000}".to_string()))
+    );
+    assert_eq!(
+      parse(r#""\400""#, EscapeTable::c()),
+      Err((true, 4, "\\400".to_string()))
+    );
+    assert_eq!(
+      parse(r#""abc"#, EscapeTable::json()),
+      Err((false, 4, "\"abc".to_string()))
+    );
+  }
+
+  #[test]
+  fn test_escaped_string_committed() {
+    let input = r#""\q""#.chars().collect::<Vec<_>>();
+    let parser = escaped_string('"', '\\', EscapeTable::json()) | tag("\"\\q\"");
+
+    let result = parser.parse(&input);
+
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Committed));
+  }
+
+  #[test]
+  fn test_raw_string() {
+    let input = r##"r"abc" r#"x"y"# r#"abc""##.chars().collect::<Vec<_>>();
+    let parser = raw_string();
+
+    let result = parser.parse(&input);
+    assert_eq!(result.consumed_count(), 6);
+    assert_eq!(result.success().unwrap(), "abc");
+
+    let result = parser.parse(&input[7..]);
+    assert_eq!(result.success().unwrap(), "x\"y");
+
+    assert!(parser.parse(&input[17..]).is_failure());
+  }
+}
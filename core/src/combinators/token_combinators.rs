@@ -0,0 +1,151 @@
+use crate::prelude::*;
+
+/// Returns the error for an unexpected token at the current position, or [ParseError::Incomplete] at the end of input.
+fn unexpected_token<'a, T, A>(parse_context: ParseContext<'a, T>, expected: Option<&T::Kind>) -> ParseResult<'a, T, A>
+where
+  T: TokenElement + 'a, {
+  let input = parse_context.original_input();
+  let offset = parse_context.next_offset();
+  let actual = match input.get(offset) {
+    Some(actual) => actual,
+    None => return ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete()),
+  };
+  let msg = match expected {
+    Some(kind) => format!("expect token: {:?}, found: {:?}", kind, actual.kind()),
+    None => format!("unexpected token: {:?}", actual.kind()),
+  };
+  let pe = ParseError::of_mismatch(input, offset, 1, msg);
+  ParseResult::failed_with_uncommitted(parse_context, pe)
+}
+
+/// Returns a [ClonableParser] that parses a token of the specified kind.
+///
+/// - kind: the discriminant of the token
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// use std::ops::Range;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Tok(char, Range<usize>);
+///
+/// impl TokenElement for Tok {
+///   type Kind = char;
+///
+///   fn kind(&self) -> char {
+///     self.0
+///   }
+///
+///   fn span(&self) -> Range<usize> {
+///     self.1.clone()
+///   }
+/// }
+///
+/// let input = [Tok('(', 0..1), Tok(')', 3..4)];
+///
+/// let parser = token('(') + token(')');
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), (&input[0], &input[1]));
+/// ```
+pub fn token<'a, T>(
+  kind: T::Kind,
+) -> Parser<'a, T, &'a T, impl Fn(ParseContext<'a, T>) -> ParseResult<'a, T, &'a T> + 'a>
+where
+  T: TokenElement + 'a,
+  T::Kind: 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, T>| {
+    match parse_context.original_input().get(parse_context.next_offset()) {
+      Some(actual) if actual.kind() == kind => ParseResult::successful(parse_context, actual, 1),
+      _ => unexpected_token(parse_context, Some(&kind)),
+    }
+  })
+}
+
+/// Returns a [ClonableParser] that parses a token for which `f` returns `Some`, and returns the value.
+///
+/// Use this to extract the payload of a token, e.g. the value of a number literal.
+///
+/// - f: a function from a token to its payload
+pub fn token_map<'a, T, A, F>(f: F) -> Parser<'a, T, A, impl Fn(ParseContext<'a, T>) -> ParseResult<'a, T, A> + 'a>
+where
+  T: TokenElement + 'a,
+  F: Fn(&'a T) -> Option<A> + 'a, {
+  Parser::new(move |parse_context: ParseContext<'a, T>| {
+    match parse_context
+      .original_input()
+      .get(parse_context.next_offset())
+      .and_then(&f)
+    {
+      Some(value) => ParseResult::successful(parse_context, value, 1),
+      None => unexpected_token(parse_context, None),
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+  use std::ops::Range;
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  enum Kind {
+    Ident,
+    Eq,
+  }
+
+  #[derive(Debug, PartialEq)]
+  struct Tok(Kind, &'static str, Range<usize>);
+
+  impl TokenElement for Tok {
+    type Kind = Kind;
+
+    fn kind(&self) -> Kind {
+      self.0
+    }
+
+    fn span(&self) -> Range<usize> {
+      self.2.clone()
+    }
+  }
+
+  #[test]
+  fn test_token_errors_use_source_spans() {
+    // "x  = y"
+    let input = [
+      Tok(Kind::Ident, "x", 0..1),
+      Tok(Kind::Eq, "=", 3..4),
+      Tok(Kind::Ident, "y", 5..6),
+    ];
+    let ident = token_map(|t: &Tok| (t.0 == Kind::Ident).then_some(t.1));
+
+    let error = (ident.clone() * ident.clone()).parse(&input).failure().unwrap();
+    assert_eq!(error.source_span(), Some(3..4));
+    assert_eq!(
+      error.display_source().to_string(),
+      "Mismatch at 3..4: unexpected token: Eq"
+    );
+
+    let error = (ident.clone() - token(Kind::Eq) + ident.clone() + ident)
+      .parse(&input)
+      .failure()
+      .unwrap();
+    assert!(error.is_in_complete());
+  }
+
+  #[test]
+  fn test_token_source_span_of_tokens() {
+    let input = [
+      Tok(Kind::Ident, "a", 2..3),
+      Tok(Kind::Ident, "b", 4..5),
+      Tok(Kind::Eq, "=", 6..7),
+    ];
+    let error = ParseError::of_mismatch(&input, 0, 2, "".to_string());
+    assert_eq!(error.source_span(), Some(2..5));
+    assert_eq!(ParseError::<Tok>::of_in_complete().source_span(), None);
+  }
+}
@@ -0,0 +1,173 @@
+use crate::prelude::*;
+
+/// Returns a [ClonableParser] that parses a Unicode alphabetic character.(for reference)
+pub fn elm_unicode_alphabetic_ref<'a, I>(
+) -> Parser<'a, I, &'a I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, &'a I> + 'a>
+where
+  I: Element + PartialEq + 'a, {
+  elm_pred_ref(Element::is_unicode_alphabetic)
+}
+
+/// Returns a [ClonableParser] that parses a Unicode alphabetic character.(for value)
+pub fn elm_unicode_alphabetic<'a, I>() -> Parser<'a, I, I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, I> + 'a>
+where
+  I: Element + PartialEq + Clone + 'a, {
+  elm_unicode_alphabetic_ref().map(Clone::clone)
+}
+
+/// Returns a [ClonableParser] that parses a Unicode numeric character.(for reference)
+pub fn elm_unicode_numeric_ref<'a, I>(
+) -> Parser<'a, I, &'a I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, &'a I> + 'a>
+where
+  I: Element + PartialEq + 'a, {
+  elm_pred_ref(Element::is_unicode_numeric)
+}
+
+/// Returns a [ClonableParser] that parses a Unicode numeric character.(for value)
+pub fn elm_unicode_numeric<'a, I>() -> Parser<'a, I, I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, I> + 'a>
+where
+  I: Element + PartialEq + Clone + 'a, {
+  elm_unicode_numeric_ref().map(Clone::clone)
+}
+
+/// Returns a [ClonableParser] that parses a Unicode whitespace character.(for reference)
+pub fn elm_unicode_whitespace_ref<'a, I>(
+) -> Parser<'a, I, &'a I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, &'a I> + 'a>
+where
+  I: Element + PartialEq + 'a, {
+  elm_pred_ref(Element::is_unicode_whitespace)
+}
+
+/// Returns a [ClonableParser] that parses a Unicode whitespace character.(for value)
+pub fn elm_unicode_whitespace<'a, I>() -> Parser<'a, I, I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, I> + 'a>
+where
+  I: Element + PartialEq + Clone + 'a, {
+  elm_unicode_whitespace_ref().map(Clone::clone)
+}
+
+/// Returns a [ClonableParser] that parses a character with the XID_Start property.(for reference)
+pub fn elm_unicode_xid_start_ref<'a, I>(
+) -> Parser<'a, I, &'a I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, &'a I> + 'a>
+where
+  I: Element + PartialEq + 'a, {
+  elm_pred_ref(Element::is_xid_start)
+}
+
+/// Returns a [ClonableParser] that parses a character with the XID_Start property.(for value)
+pub fn elm_unicode_xid_start<'a, I>() -> Parser<'a, I, I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, I> + 'a>
+where
+  I: Element + PartialEq + Clone + 'a, {
+  elm_unicode_xid_start_ref().map(Clone::clone)
+}
+
+/// Returns a [ClonableParser] that parses a character with the XID_Continue property.(for reference)
+pub fn elm_unicode_xid_continue_ref<'a, I>(
+) -> Parser<'a, I, &'a I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, &'a I> + 'a>
+where
+  I: Element + PartialEq + 'a, {
+  elm_pred_ref(Element::is_xid_continue)
+}
+
+/// Returns a [ClonableParser] that parses a character with the XID_Continue property.(for value)
+pub fn elm_unicode_xid_continue<'a, I>() -> Parser<'a, I, I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, I> + 'a>
+where
+  I: Element + PartialEq + Clone + 'a, {
+  elm_unicode_xid_continue_ref().map(Clone::clone)
+}
+
+/// Returns a [ClonableParser] that parses a character in the specified general category.(for reference)
+///
+/// - category: a general category
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text: &str = "Ωx";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = elm_unicode_category_ref(GeneralCategory::Lu);
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(*result.success().unwrap(), 'Ω');
+/// assert!(parser.parse(&input[1..]).is_failure());
+/// ```
+pub fn elm_unicode_category_ref<'a, I>(
+  category: GeneralCategory,
+) -> Parser<'a, I, &'a I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, &'a I> + 'a>
+where
+  I: Element + PartialEq + 'a, {
+  elm_pred_ref(move |c: &I| c.is_unicode_general_category(category))
+}
+
+/// Returns a [ClonableParser] that parses a character in the specified general category.(for value)
+///
+/// - category: a general category
+pub fn elm_unicode_category<'a, I>(
+  category: GeneralCategory,
+) -> Parser<'a, I, I, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, I> + 'a>
+where
+  I: Element + PartialEq + Clone + 'a, {
+  elm_unicode_category_ref(category).map(Clone::clone)
+}
+
+/// Returns a [ClonableParser] that parses an identifier following the default identifier syntax of UAX #31, i.e.
+/// `XID_Start XID_Continue*`.
+///
+/// Languages that also allow `_` at the start, such as Rust, can combine [elm_unicode_xid_continue] with their own
+/// start rule.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text: &str = "変数_1 = 0";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = identifier();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), "変数_1");
+/// ```
+pub fn identifier<'a>(
+) -> Parser<'a, char, String, impl Fn(ParseContext<'a, char>) -> ParseResult<'a, char, String> + 'a> {
+  (elm_unicode_xid_start() + elm_unicode_xid_continue().of_many0()).map(|(head, tail)| {
+    let mut identifier = String::from(head);
+    identifier.extend(tail);
+    identifier
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_identifier_rejects_non_start() {
+    let text = "_x 1x";
+    let input = text.chars().collect::<Vec<_>>();
+
+    assert!(identifier().parse(&input).is_failure());
+    assert!(identifier().parse(&input[3..]).is_failure());
+    assert_eq!(identifier().parse(&input[1..]).success().unwrap(), "x");
+  }
+
+  #[test]
+  fn test_unicode_predicates() {
+    let text = "é٣\u{3000}";
+    let input = text.chars().collect::<Vec<_>>();
+    let parser = elm_unicode_alphabetic() + elm_unicode_numeric() + elm_unicode_whitespace();
+
+    assert!(parser.parse(&input).is_success());
+    assert!(elm_unicode_alphabetic_ref::<u8>().parse("é".as_bytes()).is_failure());
+    assert_eq!(GeneralCategory::of('٣'), GeneralCategory::Nd);
+    assert_eq!(GeneralCategory::of('\u{E000}'), GeneralCategory::Co);
+    assert_eq!(GeneralCategory::of('\u{0378}'), GeneralCategory::Cn);
+  }
+}
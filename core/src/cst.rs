@@ -0,0 +1,602 @@
+use crate::prelude::*;
+use std::fmt;
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq, Eq)]
+struct GreenTokenData<K> {
+  kind: K,
+  text: String,
+  width: usize,
+  trivia: bool,
+}
+
+/// An immutable leaf of a concrete syntax tree: a token or a piece of trivia with its text.
+///
+/// Green elements know their width but not their position, so they can be shared between trees.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenToken<K> {
+  data: Rc<GreenTokenData<K>>,
+}
+
+impl<K> Clone for GreenToken<K> {
+  fn clone(&self) -> Self {
+    Self {
+      data: Rc::clone(&self.data),
+    }
+  }
+}
+
+impl<K> GreenToken<K> {
+  /// Create a token.
+  pub fn new(kind: K, text: &str) -> Self {
+    Self::with_trivia(kind, text, false)
+  }
+
+  /// Create a piece of trivia, e.g. whitespace or a comment.
+  pub fn new_trivia(kind: K, text: &str) -> Self {
+    Self::with_trivia(kind, text, true)
+  }
+
+  fn with_trivia(kind: K, text: &str, trivia: bool) -> Self {
+    let data = GreenTokenData {
+      kind,
+      text: text.to_string(),
+      width: text.chars().count(),
+      trivia,
+    };
+    Self { data: Rc::new(data) }
+  }
+
+  pub fn kind(&self) -> &K {
+    &self.data.kind
+  }
+
+  pub fn text(&self) -> &str {
+    &self.data.text
+  }
+
+  /// Returns the number of chars of the text.
+  pub fn width(&self) -> usize {
+    self.data.width
+  }
+
+  pub fn is_trivia(&self) -> bool {
+    self.data.trivia
+  }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct GreenNodeData<K> {
+  kind: K,
+  width: usize,
+  children: Vec<GreenElement<K>>,
+}
+
+/// An immutable inner node of a concrete syntax tree.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenNode<K> {
+  data: Rc<GreenNodeData<K>>,
+}
+
+impl<K> Clone for GreenNode<K> {
+  fn clone(&self) -> Self {
+    Self {
+      data: Rc::clone(&self.data),
+    }
+  }
+}
+
+impl<K> GreenNode<K> {
+  /// Create a node from its children.
+  pub fn new(kind: K, children: Vec<GreenElement<K>>) -> Self {
+    let width = children.iter().map(GreenElement::width).sum();
+    Self {
+      data: Rc::new(GreenNodeData { kind, width, children }),
+    }
+  }
+
+  pub fn kind(&self) -> &K {
+    &self.data.kind
+  }
+
+  /// Returns the number of chars covered by the node.
+  pub fn width(&self) -> usize {
+    self.data.width
+  }
+
+  pub fn children(&self) -> &[GreenElement<K>] {
+    &self.data.children
+  }
+
+  /// Whether `self` and `other` are the same allocation, i.e. one was reused for the other.
+  pub fn ptr_eq(&self, other: &Self) -> bool {
+    Rc::ptr_eq(&self.data, &other.data)
+  }
+
+  fn write_text(&self, text: &mut String) {
+    for child in self.children() {
+      match child {
+        GreenElement::Node(node) => node.write_text(text),
+        GreenElement::Token(token) => text.push_str(token.text()),
+      }
+    }
+  }
+
+  /// Returns the concatenated text of the leaves.
+  pub fn text(&self) -> String {
+    let mut text = String::new();
+    self.write_text(&mut text);
+    text
+  }
+}
+
+impl<K> Display for GreenNode<K> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.text())
+  }
+}
+
+/// A child of a [GreenNode].
+#[derive(Debug, PartialEq, Eq)]
+pub enum GreenElement<K> {
+  Node(GreenNode<K>),
+  Token(GreenToken<K>),
+}
+
+impl<K> Clone for GreenElement<K> {
+  fn clone(&self) -> Self {
+    match self {
+      GreenElement::Node(node) => GreenElement::Node(node.clone()),
+      GreenElement::Token(token) => GreenElement::Token(token.clone()),
+    }
+  }
+}
+
+impl<K> GreenElement<K> {
+  pub fn kind(&self) -> &K {
+    match self {
+      GreenElement::Node(node) => node.kind(),
+      GreenElement::Token(token) => token.kind(),
+    }
+  }
+
+  pub fn width(&self) -> usize {
+    match self {
+      GreenElement::Node(node) => node.width(),
+      GreenElement::Token(token) => token.width(),
+    }
+  }
+}
+
+/// A value that contributes children to a [GreenNode].
+///
+/// Implemented for green elements, and for the tuples, vectors and options that combinators such as `+`, `of_many0`
+/// and `opt` build from them, so a parser of any such shape can be wrapped with [CstBuilder::node].
+pub trait IntoGreen<K> {
+  /// Append the green elements of `self` to `children`.
+  fn push_green(self, children: &mut Vec<GreenElement<K>>);
+}
+
+impl<K> IntoGreen<K> for GreenElement<K> {
+  fn push_green(self, children: &mut Vec<GreenElement<K>>) {
+    children.push(self);
+  }
+}
+
+impl<K> IntoGreen<K> for GreenNode<K> {
+  fn push_green(self, children: &mut Vec<GreenElement<K>>) {
+    children.push(GreenElement::Node(self));
+  }
+}
+
+impl<K> IntoGreen<K> for GreenToken<K> {
+  fn push_green(self, children: &mut Vec<GreenElement<K>>) {
+    children.push(GreenElement::Token(self));
+  }
+}
+
+impl<K> IntoGreen<K> for () {
+  fn push_green(self, _: &mut Vec<GreenElement<K>>) {}
+}
+
+impl<K, T: IntoGreen<K>> IntoGreen<K> for Option<T> {
+  fn push_green(self, children: &mut Vec<GreenElement<K>>) {
+    if let Some(value) = self {
+      value.push_green(children);
+    }
+  }
+}
+
+impl<K, T: IntoGreen<K>> IntoGreen<K> for Vec<T> {
+  fn push_green(self, children: &mut Vec<GreenElement<K>>) {
+    for value in self {
+      value.push_green(children);
+    }
+  }
+}
+
+impl<K, A: IntoGreen<K>, B: IntoGreen<K>> IntoGreen<K> for (A, B) {
+  fn push_green(self, children: &mut Vec<GreenElement<K>>) {
+    self.0.push_green(children);
+    self.1.push_green(children);
+  }
+}
+
+struct SyntaxNodeData<K> {
+  green: GreenNode<K>,
+  offset: usize,
+  parent: Option<SyntaxNode<K>>,
+}
+
+/// A view of a [GreenNode] with its position and parent, for navigating a concrete syntax tree.
+///
+/// Offsets count chars from the start of the root.
+pub struct SyntaxNode<K> {
+  data: Rc<SyntaxNodeData<K>>,
+}
+
+impl<K> Clone for SyntaxNode<K> {
+  fn clone(&self) -> Self {
+    Self {
+      data: Rc::clone(&self.data),
+    }
+  }
+}
+
+impl<K> PartialEq for SyntaxNode<K> {
+  fn eq(&self, other: &Self) -> bool {
+    self.data.offset == other.data.offset && self.data.green.ptr_eq(&other.data.green)
+  }
+}
+
+impl<K: Debug> Debug for SyntaxNode<K> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:?}@{:?}", self.kind(), self.text_range())
+  }
+}
+
+impl<K> SyntaxNode<K> {
+  /// Create the root of a tree.
+  pub fn new_root(green: GreenNode<K>) -> Self {
+    Self::new(green, 0, None)
+  }
+
+  fn new(green: GreenNode<K>, offset: usize, parent: Option<SyntaxNode<K>>) -> Self {
+    Self {
+      data: Rc::new(SyntaxNodeData { green, offset, parent }),
+    }
+  }
+
+  pub fn kind(&self) -> &K {
+    self.data.green.kind()
+  }
+
+  pub fn green(&self) -> &GreenNode<K> {
+    &self.data.green
+  }
+
+  pub fn text_range(&self) -> Range<usize> {
+    self.data.offset..self.data.offset + self.data.green.width()
+  }
+
+  pub fn text(&self) -> String {
+    self.data.green.text()
+  }
+
+  pub fn parent(&self) -> Option<SyntaxNode<K>> {
+    self.data.parent.clone()
+  }
+
+  /// Returns the child nodes and tokens.
+  pub fn children_with_tokens(&self) -> Vec<SyntaxElement<K>> {
+    let mut offset = self.data.offset;
+    let mut elements = vec![];
+    for child in self.data.green.children() {
+      elements.push(match child {
+        GreenElement::Node(node) => SyntaxElement::Node(SyntaxNode::new(node.clone(), offset, Some(self.clone()))),
+        GreenElement::Token(token) => SyntaxElement::Token(SyntaxToken {
+          green: token.clone(),
+          offset,
+          parent: self.clone(),
+        }),
+      });
+      offset += child.width();
+    }
+    elements
+  }
+
+  /// Returns the child nodes.
+  pub fn children(&self) -> Vec<SyntaxNode<K>> {
+    let elements = self.children_with_tokens().into_iter();
+    elements
+      .filter_map(|element| match element {
+        SyntaxElement::Node(node) => Some(node),
+        SyntaxElement::Token(_) => None,
+      })
+      .collect()
+  }
+
+  /// Returns all tokens under this node in order, including trivia.
+  pub fn tokens(&self) -> Vec<SyntaxToken<K>> {
+    let mut tokens = vec![];
+    for element in self.children_with_tokens() {
+      match element {
+        SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+        SyntaxElement::Token(token) => tokens.push(token),
+      }
+    }
+    tokens
+  }
+}
+
+/// A view of a [GreenToken] with its position and parent.
+pub struct SyntaxToken<K> {
+  green: GreenToken<K>,
+  offset: usize,
+  parent: SyntaxNode<K>,
+}
+
+impl<K> Clone for SyntaxToken<K> {
+  fn clone(&self) -> Self {
+    Self {
+      green: self.green.clone(),
+      offset: self.offset,
+      parent: self.parent.clone(),
+    }
+  }
+}
+
+impl<K: Debug> Debug for SyntaxToken<K> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:?}@{:?} {:?}", self.kind(), self.text_range(), self.text())
+  }
+}
+
+impl<K> SyntaxToken<K> {
+  pub fn kind(&self) -> &K {
+    self.green.kind()
+  }
+
+  pub fn green(&self) -> &GreenToken<K> {
+    &self.green
+  }
+
+  pub fn text(&self) -> &str {
+    self.green.text()
+  }
+
+  pub fn text_range(&self) -> Range<usize> {
+    self.offset..self.offset + self.green.width()
+  }
+
+  pub fn is_trivia(&self) -> bool {
+    self.green.is_trivia()
+  }
+
+  pub fn parent(&self) -> SyntaxNode<K> {
+    self.parent.clone()
+  }
+}
+
+/// A child of a [SyntaxNode].
+#[derive(Debug, Clone)]
+pub enum SyntaxElement<K> {
+  Node(SyntaxNode<K>),
+  Token(SyntaxToken<K>),
+}
+
+/// Builds a lossless concrete syntax tree while parsing `char` input.
+///
+/// [CstBuilder::token] turns the text consumed by any parser into a [GreenToken], followed by the trivia after it,
+/// and [CstBuilder::node] wraps the tokens and nodes produced by a parser into a [GreenNode]. As long as every
+/// consumed char goes through a token or trivia, concatenating the leaves of the tree reproduces the input exactly.
+///
+/// - trivia_kind: the kind of trivia tokens
+/// - trivia: a parser of whitespace and comments; an uncommitted failure counts as no trivia
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Kind {
+///   Trivia,
+///   Ident,
+///   Eq,
+///   Number,
+///   Assign,
+///   Root,
+/// }
+///
+/// let text = " x = 1 // one\ny=2\n";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let lexer = Lexer::new().with_line_comment("//");
+/// let cst = CstBuilder::new(Kind::Trivia, lexer.trivia());
+///
+/// let ident = cst.token(Kind::Ident, elm_alpha_ref().of_many1());
+/// let number = cst.token(Kind::Number, elm_digit_ref().of_many1());
+/// let assign = cst.node(Kind::Assign, ident + cst.token(Kind::Eq, tag("=")) + number);
+/// let root = cst.root(Kind::Root, assign.of_many0()) - end();
+///
+/// let green = root.parse(&input).success().unwrap();
+/// assert_eq!(green.text(), text);
+///
+/// let tree = SyntaxNode::new_root(green);
+/// let assigns = tree.children();
+/// assert_eq!(assigns[1].text_range(), 14..18);
+///
+/// let tokens = assigns[0].tokens();
+/// assert_eq!(tokens[1].text(), " ");
+/// assert!(tokens[1].is_trivia());
+/// assert_eq!(tokens[5].text(), " // one\n");
+/// ```
+pub struct CstBuilder<'a, K, B, P>
+where
+  P: ParserRunner<'a, char, B>, {
+  trivia_kind: K,
+  trivia: P,
+  _phantom: PhantomData<&'a B>,
+}
+
+impl<'a, K, B, P> CstBuilder<'a, K, B, P>
+where
+  K: Clone + 'a,
+  B: 'a,
+  P: ParserRunner<'a, char, B>,
+{
+  /// Create a builder with the trivia between tokens.
+  pub fn new(trivia_kind: K, trivia: P) -> Self {
+    Self {
+      trivia_kind,
+      trivia,
+      _phantom: PhantomData,
+    }
+  }
+
+  /// Returns a [Parser] of the trivia at the current position, as a trivia token if any.
+  pub fn trivia(&self) -> Parser<'a, char, Option<GreenToken<K>>, impl ParserFn<'a, char, Option<GreenToken<K>>>> {
+    let (kind, trivia) = (self.trivia_kind.clone(), self.trivia.clone());
+    Parser::new(
+      move |parse_context: ParseContext<'a, char>| match trivia.run(parse_context.with_same_state()) {
+        ParseResult::Success { length, .. } if length > 0 => {
+          let text = String::from_iter(parse_context.slice_with_len(length));
+          ParseResult::successful(parse_context, Some(GreenToken::new_trivia(kind.clone(), &text)), length)
+        }
+        ParseResult::Failure {
+          parse_context,
+          error,
+          committed_status,
+        } if committed_status.is_committed() => ParseResult::failed(parse_context, error, committed_status),
+        _ => ParseResult::successful(parse_context, None, 0),
+      },
+    )
+  }
+
+  /// Returns a [Parser] that turns the text consumed by `parser` into a token, followed by the trivia after it.
+  ///
+  /// - kind: the kind of the token
+  /// - parser: a parser of the token text
+  pub fn token<A, Q>(
+    &self,
+    kind: K,
+    parser: Q,
+  ) -> Parser<'a, char, Vec<GreenElement<K>>, impl ParserFn<'a, char, Vec<GreenElement<K>>>>
+  where
+    A: 'a,
+    Q: ParserRunner<'a, char, A> + 'a, {
+    let trivia = self.trivia();
+    Parser::new(move |parse_context: ParseContext<'a, char>| {
+      let length = match parser.run(parse_context.with_same_state()) {
+        ParseResult::Success { length, .. } => length,
+        ParseResult::Failure {
+          parse_context,
+          error,
+          committed_status,
+        } => return ParseResult::failed(parse_context, error, committed_status),
+      };
+      let text = String::from_iter(parse_context.slice_with_len(length));
+      let mut elements = vec![GreenElement::Token(GreenToken::new(kind.clone(), &text))];
+      match trivia.run(parse_context.add_offset(length)) {
+        ParseResult::Success {
+          value: trivia,
+          length: trivia_length,
+          ..
+        } => {
+          trivia.push_green(&mut elements);
+          ParseResult::successful(parse_context, elements, length + trivia_length)
+        }
+        ParseResult::Failure {
+          parse_context,
+          error,
+          committed_status,
+        } => ParseResult::failed(parse_context, error, committed_status),
+      }
+    })
+  }
+
+  /// Returns a [Parser] that wraps the tokens and nodes produced by `parser` into a node.
+  ///
+  /// - kind: the kind of the node
+  /// - parser: a parser of the children
+  pub fn node<A, Q>(
+    &self,
+    kind: K,
+    parser: Q,
+  ) -> Parser<'a, char, GreenNode<K>, impl ParserFn<'a, char, GreenNode<K>>>
+  where
+    A: IntoGreen<K> + 'a,
+    Q: ParserRunner<'a, char, A> + 'a, {
+    parser.map(move |value| {
+      let mut children = vec![];
+      value.push_green(&mut children);
+      GreenNode::new(kind.clone(), children)
+    })
+  }
+
+  /// Returns a [Parser] of a root node: the leading trivia followed by the children produced by `parser`.
+  ///
+  /// - kind: the kind of the root node
+  /// - parser: a parser of the children
+  pub fn root<A, Q>(
+    &self,
+    kind: K,
+    parser: Q,
+  ) -> Parser<'a, char, GreenNode<K>, impl ParserFn<'a, char, GreenNode<K>>>
+  where
+    A: IntoGreen<K> + 'a,
+    Q: ParserRunner<'a, char, A> + 'a, {
+    self.node(kind, self.trivia().and_then(parser))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[derive(Debug, Clone, PartialEq)]
+  enum Kind {
+    Ws,
+    Word,
+    Comma,
+    List,
+  }
+
+  fn list<'a>() -> impl ParserRunner<'a, char, GreenNode<Kind>> {
+    let cst = CstBuilder::new(Kind::Ws, elm_multi_space_ref().of_many1());
+    let word = cst.token(Kind::Word, elm_alpha_ref().of_many1());
+    let rest = (cst.token(Kind::Comma, elm_ref(',')) + word.clone()).of_many0();
+    cst.root(Kind::List, word + rest) - end()
+  }
+
+  #[test]
+  fn test_cst_is_lossless() {
+    for text in ["a", "  a ,b,\n c  ", "a , b"] {
+      let input = text.chars().collect::<Vec<_>>();
+      let green = list().parse(&input).success().unwrap();
+
+      assert_eq!(green.text(), text);
+      assert_eq!(green.width(), input.len());
+      let tree = SyntaxNode::new_root(green);
+      let leaves = tree.tokens().iter().map(|t| t.text().to_string()).collect::<String>();
+      assert_eq!(leaves, text);
+    }
+  }
+
+  #[test]
+  fn test_cst_navigation() {
+    let input = "x , y".chars().collect::<Vec<_>>();
+    let tree = SyntaxNode::new_root(list().parse(&input).success().unwrap());
+
+    let tokens = tree.tokens();
+    let kinds = tokens.iter().map(|t| t.kind().clone()).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![Kind::Word, Kind::Ws, Kind::Comma, Kind::Ws, Kind::Word]);
+    assert_eq!(tokens[4].text_range(), 4..5);
+    assert_eq!(tokens[4].parent(), tree);
+    assert!(tokens.iter().filter(|t| t.is_trivia()).all(|t| t.text() == " "));
+  }
+}
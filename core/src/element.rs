@@ -1,3 +1,4 @@
+use crate::general_category::GeneralCategory;
 use std::fmt::Debug;
 
 /// A Element.<br/>
@@ -37,6 +38,38 @@ pub trait Element: Debug {
   fn is_ascii_punctuation(&self) -> bool;
   fn is_ascii_graphic(&self) -> bool;
   fn is_ascii_control(&self) -> bool;
+
+  // Unicodeの分類は既定では`false`を返すので、これらを持たない既存の実装もそのまま使える。
+
+  /// Unicodeのアルファベット(Alphabetic)かどうか。
+  fn is_unicode_alphabetic(&self) -> bool {
+    false
+  }
+
+  /// Unicodeの数字(Numeric)かどうか。
+  fn is_unicode_numeric(&self) -> bool {
+    false
+  }
+
+  /// Unicodeの空白(White_Space)かどうか。
+  fn is_unicode_whitespace(&self) -> bool {
+    false
+  }
+
+  /// 識別子の先頭文字(XID_Start)かどうか。
+  fn is_xid_start(&self) -> bool {
+    false
+  }
+
+  /// 識別子の後続文字(XID_Continue)かどうか。
+  fn is_xid_continue(&self) -> bool {
+    false
+  }
+
+  /// 指定した一般カテゴリに属するかどうか。
+  fn is_unicode_general_category(&self, _category: GeneralCategory) -> bool {
+    false
+  }
 }
 
 impl Element for u8 {
@@ -107,6 +140,31 @@ impl Element for u8 {
   fn is_ascii_control(&self) -> bool {
     matches!(*self, b'\0'..=b'\x1F' | b'\x7F')
   }
+
+  // 非ASCIIのバイトはUTF-8の一部なので、どのUnicodeの分類にも属さない。
+  fn is_unicode_alphabetic(&self) -> bool {
+    self.is_ascii() && char::from(*self).is_alphabetic()
+  }
+
+  fn is_unicode_numeric(&self) -> bool {
+    self.is_ascii() && char::from(*self).is_numeric()
+  }
+
+  fn is_unicode_whitespace(&self) -> bool {
+    self.is_ascii() && char::from(*self).is_whitespace()
+  }
+
+  fn is_xid_start(&self) -> bool {
+    self.is_ascii() && unicode_ident::is_xid_start(char::from(*self))
+  }
+
+  fn is_xid_continue(&self) -> bool {
+    self.is_ascii() && unicode_ident::is_xid_continue(char::from(*self))
+  }
+
+  fn is_unicode_general_category(&self, category: GeneralCategory) -> bool {
+    self.is_ascii() && category.contains(char::from(*self))
+  }
 }
 
 impl Element for char {
@@ -177,6 +235,30 @@ impl Element for char {
   fn is_ascii_control(&self) -> bool {
     matches!(*self, '\0'..='\x1F' | '\x7F')
   }
+
+  fn is_unicode_alphabetic(&self) -> bool {
+    self.is_alphabetic()
+  }
+
+  fn is_unicode_numeric(&self) -> bool {
+    self.is_numeric()
+  }
+
+  fn is_unicode_whitespace(&self) -> bool {
+    self.is_whitespace()
+  }
+
+  fn is_xid_start(&self) -> bool {
+    unicode_ident::is_xid_start(*self)
+  }
+
+  fn is_xid_continue(&self) -> bool {
+    unicode_ident::is_xid_continue(*self)
+  }
+
+  fn is_unicode_general_category(&self, category: GeneralCategory) -> bool {
+    category.contains(*self)
+  }
 }
 
 impl Element for &char {
@@ -247,4 +329,28 @@ impl Element for &char {
   fn is_ascii_control(&self) -> bool {
     matches!(**self, '\0'..='\x1F' | '\x7F')
   }
+
+  fn is_unicode_alphabetic(&self) -> bool {
+    (**self).is_alphabetic()
+  }
+
+  fn is_unicode_numeric(&self) -> bool {
+    (**self).is_numeric()
+  }
+
+  fn is_unicode_whitespace(&self) -> bool {
+    (**self).is_whitespace()
+  }
+
+  fn is_xid_start(&self) -> bool {
+    unicode_ident::is_xid_start(**self)
+  }
+
+  fn is_xid_continue(&self) -> bool {
+    unicode_ident::is_xid_continue(**self)
+  }
+
+  fn is_unicode_general_category(&self, category: GeneralCategory) -> bool {
+    category.contains(**self)
+  }
 }
@@ -0,0 +1,188 @@
+use regex_syntax::hir::{Class, HirKind};
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+/// A Unicode general category.<br/>
+/// Unicodeの一般カテゴリ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GeneralCategory {
+  /// Uppercase_Letter
+  Lu,
+  /// Lowercase_Letter
+  Ll,
+  /// Titlecase_Letter
+  Lt,
+  /// Modifier_Letter
+  Lm,
+  /// Other_Letter
+  Lo,
+  /// Nonspacing_Mark
+  Mn,
+  /// Spacing_Mark
+  Mc,
+  /// Enclosing_Mark
+  Me,
+  /// Decimal_Number
+  Nd,
+  /// Letter_Number
+  Nl,
+  /// Other_Number
+  No,
+  /// Connector_Punctuation
+  Pc,
+  /// Dash_Punctuation
+  Pd,
+  /// Open_Punctuation
+  Ps,
+  /// Close_Punctuation
+  Pe,
+  /// Initial_Punctuation
+  Pi,
+  /// Final_Punctuation
+  Pf,
+  /// Other_Punctuation
+  Po,
+  /// Math_Symbol
+  Sm,
+  /// Currency_Symbol
+  Sc,
+  /// Modifier_Symbol
+  Sk,
+  /// Other_Symbol
+  So,
+  /// Space_Separator
+  Zs,
+  /// Line_Separator
+  Zl,
+  /// Paragraph_Separator
+  Zp,
+  /// Control
+  Cc,
+  /// Format
+  Cf,
+  /// Surrogate, which no `char` belongs to
+  Cs,
+  /// Private_Use
+  Co,
+  /// Unassigned
+  Cn,
+}
+
+impl GeneralCategory {
+  /// All general categories.
+  pub const ALL: [GeneralCategory; 30] = [
+    Self::Lu,
+    Self::Ll,
+    Self::Lt,
+    Self::Lm,
+    Self::Lo,
+    Self::Mn,
+    Self::Mc,
+    Self::Me,
+    Self::Nd,
+    Self::Nl,
+    Self::No,
+    Self::Pc,
+    Self::Pd,
+    Self::Ps,
+    Self::Pe,
+    Self::Pi,
+    Self::Pf,
+    Self::Po,
+    Self::Sm,
+    Self::Sc,
+    Self::Sk,
+    Self::So,
+    Self::Zs,
+    Self::Zl,
+    Self::Zp,
+    Self::Cc,
+    Self::Cf,
+    Self::Cs,
+    Self::Co,
+    Self::Cn,
+  ];
+
+  /// Returns the general category of `c`.
+  pub fn of(c: char) -> Self {
+    Self::ALL
+      .into_iter()
+      .find(|category| category.contains(c))
+      .unwrap_or(Self::Cn)
+  }
+
+  /// Returns whether `c` belongs to this category.
+  pub fn contains(self, c: char) -> bool {
+    let ranges = &tables()[self as usize];
+    ranges
+      .binary_search_by(|(start, end)| {
+        if *end < c {
+          std::cmp::Ordering::Less
+        } else if *start > c {
+          std::cmp::Ordering::Greater
+        } else {
+          std::cmp::Ordering::Equal
+        }
+      })
+      .is_ok()
+  }
+
+  /// Returns the two-letter abbreviation, e.g. `Lu`.
+  pub fn abbreviation(self) -> &'static str {
+    match self {
+      Self::Lu => "Lu",
+      Self::Ll => "Ll",
+      Self::Lt => "Lt",
+      Self::Lm => "Lm",
+      Self::Lo => "Lo",
+      Self::Mn => "Mn",
+      Self::Mc => "Mc",
+      Self::Me => "Me",
+      Self::Nd => "Nd",
+      Self::Nl => "Nl",
+      Self::No => "No",
+      Self::Pc => "Pc",
+      Self::Pd => "Pd",
+      Self::Ps => "Ps",
+      Self::Pe => "Pe",
+      Self::Pi => "Pi",
+      Self::Pf => "Pf",
+      Self::Po => "Po",
+      Self::Sm => "Sm",
+      Self::Sc => "Sc",
+      Self::Sk => "Sk",
+      Self::So => "So",
+      Self::Zs => "Zs",
+      Self::Zl => "Zl",
+      Self::Zp => "Zp",
+      Self::Cc => "Cc",
+      Self::Cf => "Cf",
+      Self::Cs => "Cs",
+      Self::Co => "Co",
+      Self::Cn => "Cn",
+    }
+  }
+}
+
+impl Display for GeneralCategory {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.abbreviation())
+  }
+}
+
+/// Range tables indexed by [GeneralCategory], built once from the tables of `regex-syntax`.
+fn tables() -> &'static [Vec<(char, char)>] {
+  static TABLES: OnceLock<Vec<Vec<(char, char)>>> = OnceLock::new();
+  TABLES.get_or_init(|| {
+    GeneralCategory::ALL
+      .iter()
+      .map(|category| {
+        let pattern = format!(r"\p{{gc={}}}", category.abbreviation());
+        match regex_syntax::parse(&pattern).map(|hir| hir.into_kind()) {
+          Ok(HirKind::Class(Class::Unicode(class))) => class.ranges().iter().map(|r| (r.start(), r.end())).collect(),
+          _ => vec![],
+        }
+      })
+      .collect()
+  })
+}
@@ -0,0 +1,327 @@
+use crate::cst::{GreenElement, GreenNode};
+use crate::parse_context::ParseContext;
+use crate::parse_error::ParseError;
+use crate::parse_result::ParseResult;
+use std::ops::Range;
+use std::rc::Rc;
+
+type RuleFn<K> = dyn for<'a> Fn(ParseContext<'a, char>) -> ParseResult<'a, char, GreenNode<K>>;
+
+/// A change of text: the chars in `range` are replaced with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+  /// The replaced range, in chars of the old text.
+  pub range: Range<usize>,
+  /// The new text of the range.
+  pub replacement: String,
+}
+
+impl TextEdit {
+  pub fn new(range: Range<usize>, replacement: &str) -> Self {
+    Self {
+      range,
+      replacement: replacement.to_string(),
+    }
+  }
+
+  /// Returns `text` with this edit applied.
+  pub fn apply(&self, text: &[char]) -> Vec<char> {
+    let mut result = text[..self.range.start].to_vec();
+    result.extend(self.replacement.chars());
+    result.extend_from_slice(&text[self.range.end..]);
+    result
+  }
+
+  /// Returns the change of the text length.
+  fn delta(&self) -> isize {
+    self.replacement.chars().count() as isize - self.range.len() as isize
+  }
+}
+
+/// The result of [IncrementalParser::reparse].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reparse<K> {
+  /// The new tree.
+  pub tree: GreenNode<K>,
+  /// The range of the new text whose subtree was parsed again. Everything else is shared with the old tree.
+  pub changed: Range<usize>,
+}
+
+/// Re-parses edited text by running only the named rules whose nodes contain the edit.
+///
+/// A rule is a parser producing a [GreenNode] of its kind, usually built with
+/// [CstBuilder](crate::prelude::CstBuilder). Rules are registered as functions that work for inputs of any lifetime,
+/// e.g. `|pc| block().run(pc)` where `block` builds the parser.
+///
+/// [IncrementalParser::reparse] looks for the smallest node of the old tree that contains the edit and has a
+/// registered rule, runs that rule at the node's position in the new text, and accepts the result if it covers
+/// exactly the edited node. Otherwise it tries the enclosing rule nodes, up to the root. The new node is spliced into
+/// the old tree, so every subtree outside it is reused as is.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Kind {
+///   Ws,
+///   Word,
+///   Brace,
+///   Block,
+///   Root,
+/// }
+///
+/// fn cst<'a>() -> CstBuilder<'a, Kind, Vec<&'a char>, impl ParserRunner<'a, char, Vec<&'a char>>> {
+///   CstBuilder::new(Kind::Ws, elm_space_ref().of_many1())
+/// }
+///
+/// fn block<'a>() -> impl ParserRunner<'a, char, GreenNode<Kind>> {
+///   let cst = cst();
+///   let words = cst.token(Kind::Word, elm_alpha_ref().of_many1()).of_many0();
+///   cst.node(Kind::Block, cst.token(Kind::Brace, elm_ref('{')) + words + cst.token(Kind::Brace, elm_ref('}')))
+/// }
+///
+/// fn root<'a>() -> impl ParserRunner<'a, char, GreenNode<Kind>> {
+///   cst().root(Kind::Root, block().of_many0()) - end()
+/// }
+///
+/// let parser = IncrementalParser::new(Kind::Root, |pc| root().run(pc)).with_rule(Kind::Block, |pc| block().run(pc));
+///
+/// let old_text = "{a b} {c}".chars().collect::<Vec<_>>();
+/// let old_tree = parser.parse(&old_text).unwrap();
+///
+/// let edit = TextEdit::new(7..8, "cd e");
+/// let new_text = edit.apply(&old_text);
+/// let reparse = parser.reparse(&old_tree, &edit, &new_text).unwrap();
+///
+/// assert_eq!(reparse.tree.text(), "{a b} {cd e}");
+/// assert_eq!(reparse.changed, 6..12);
+/// ```
+pub struct IncrementalParser<K> {
+  root_kind: K,
+  root: Rc<RuleFn<K>>,
+  rules: Vec<(K, Rc<RuleFn<K>>)>,
+}
+
+impl<K> Clone for IncrementalParser<K>
+where
+  K: Clone,
+{
+  fn clone(&self) -> Self {
+    Self {
+      root_kind: self.root_kind.clone(),
+      root: Rc::clone(&self.root),
+      rules: self.rules.clone(),
+    }
+  }
+}
+
+impl<K> IncrementalParser<K>
+where
+  K: Clone + PartialEq,
+{
+  /// Create a parser with the rule for the whole text.
+  ///
+  /// - root_kind: the kind of the root node
+  /// - root: a rule that parses the whole text
+  pub fn new<F>(root_kind: K, root: F) -> Self
+  where
+    F: for<'a> Fn(ParseContext<'a, char>) -> ParseResult<'a, char, GreenNode<K>> + 'static, {
+    Self {
+      root_kind,
+      root: Rc::new(root),
+      rules: vec![],
+    }
+  }
+
+  /// Register the rule that parses nodes of `kind` on their own.
+  pub fn with_rule<F>(mut self, kind: K, rule: F) -> Self
+  where
+    F: for<'a> Fn(ParseContext<'a, char>) -> ParseResult<'a, char, GreenNode<K>> + 'static, {
+    self.rules.push((kind, Rc::new(rule)));
+    self
+  }
+
+  fn rule(&self, kind: &K) -> Option<&RuleFn<K>> {
+    if *kind == self.root_kind {
+      return Some(&*self.root);
+    }
+    self.rules.iter().find(|(k, _)| k == kind).map(|(_, rule)| &**rule)
+  }
+
+  /// Parse the whole `input` with the root rule.
+  pub fn parse<'a>(&self, input: &'a [char]) -> Result<GreenNode<K>, ParseError<'a, char>> {
+    match (self.root)(ParseContext::new(input, 0)) {
+      ParseResult::Success { value, length, .. } if length == input.len() => Ok(value),
+      ParseResult::Success { length, .. } => {
+        let msg = format!("root rule stopped at {} of {}", length, input.len());
+        Err(ParseError::of_mismatch(input, length, input.len() - length, msg))
+      }
+      ParseResult::Failure { error, .. } => Err(error),
+    }
+  }
+
+  /// Re-parse `input`, the text of `tree` after `edit`, reusing the subtrees of `tree` outside the edit.
+  ///
+  /// Falls back to [IncrementalParser::parse] if no rule node around the edit can be parsed again on its own.
+  pub fn reparse<'a>(
+    &self,
+    tree: &GreenNode<K>,
+    edit: &TextEdit,
+    input: &'a [char],
+  ) -> Result<Reparse<K>, ParseError<'a, char>> {
+    let consistent = edit.range.end <= tree.width() && tree.width() as isize + edit.delta() == input.len() as isize;
+    if consistent {
+      let path = path_to_edit(tree, &edit.range);
+      for depth in (1..path.len()).rev() {
+        let (node, offset) = &path[depth];
+        if let Some(reparsed) = self.try_rule(node, *offset, edit, input) {
+          let offsets = path[1..=depth].iter().map(|(_, offset)| *offset).collect::<Vec<_>>();
+          return Ok(Reparse {
+            tree: splice(tree, 0, &offsets, reparsed.tree),
+            changed: reparsed.changed,
+          });
+        }
+      }
+    }
+    let tree = self.parse(input)?;
+    let changed = 0..input.len();
+    Ok(Reparse { tree, changed })
+  }
+
+  /// Runs the rule of `node` at `offset` of `input`, and returns the new node if it covers exactly the edited node.
+  fn try_rule(&self, node: &GreenNode<K>, offset: usize, edit: &TextEdit, input: &[char]) -> Option<Reparse<K>> {
+    let rule = self.rule(node.kind())?;
+    let width = (node.width() as isize + edit.delta()) as usize;
+    match rule(ParseContext::new(input, offset)) {
+      ParseResult::Success { value, length, .. } if length == width && value.kind() == node.kind() => Some(Reparse {
+        tree: value,
+        changed: offset..offset + width,
+      }),
+      _ => None,
+    }
+  }
+}
+
+/// Returns the nodes from the root down to the smallest node containing `range`, with their offsets.
+fn path_to_edit<K>(tree: &GreenNode<K>, range: &Range<usize>) -> Vec<(GreenNode<K>, usize)> {
+  let mut path = vec![(tree.clone(), 0)];
+  'descend: loop {
+    let (node, mut offset) = path.last().cloned().unwrap();
+    for child in node.children() {
+      let end = offset + child.width();
+      if let GreenElement::Node(child) = child {
+        if offset <= range.start && range.end <= end {
+          path.push((child.clone(), offset));
+          continue 'descend;
+        }
+      }
+      offset = end;
+    }
+    return path;
+  }
+}
+
+/// Returns `node` with the descendant at the child `offsets` replaced by `replacement`.
+fn splice<K: Clone>(node: &GreenNode<K>, offset: usize, offsets: &[usize], replacement: GreenNode<K>) -> GreenNode<K> {
+  let Some((&target, rest)) = offsets.split_first() else {
+    return replacement;
+  };
+  let mut child_offset = offset;
+  let mut replacement = Some(replacement);
+  let children = node.children().iter().map(|child| {
+    let element = match child {
+      GreenElement::Node(child) if child_offset == target && replacement.is_some() => {
+        GreenElement::Node(splice(child, child_offset, rest, replacement.take().unwrap()))
+      }
+      _ => child.clone(),
+    };
+    child_offset += child.width();
+    element
+  });
+  GreenNode::new(node.kind().clone(), children.collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[derive(Debug, Clone, PartialEq)]
+  enum Kind {
+    Ws,
+    Word,
+    Punct,
+    List,
+    Group,
+    Root,
+  }
+
+  fn cst<'a>() -> CstBuilder<'a, Kind, Vec<&'a char>, impl ParserRunner<'a, char, Vec<&'a char>>> {
+    CstBuilder::new(Kind::Ws, elm_space_ref().of_many1())
+  }
+
+  fn list<'a>() -> impl ParserRunner<'a, char, GreenNode<Kind>> {
+    let cst = cst();
+    let words = cst.token(Kind::Word, elm_alpha_ref().of_many1()).of_many0();
+    cst.node(
+      Kind::List,
+      cst.token(Kind::Punct, elm_ref('(')) + words + cst.token(Kind::Punct, elm_ref(')')),
+    )
+  }
+
+  fn group<'a>() -> impl ParserRunner<'a, char, GreenNode<Kind>> {
+    let cst = cst();
+    // Calling `list` through a closure keeps the nested parser types small enough to type-check quickly.
+    let lists = Parser::new(|pc| list().run(pc)).of_many0();
+    cst.node(
+      Kind::Group,
+      cst.token(Kind::Punct, elm_ref('[')) + lists + cst.token(Kind::Punct, elm_ref(']')),
+    )
+  }
+
+  fn parser() -> IncrementalParser<Kind> {
+    IncrementalParser::new(Kind::Root, |pc| {
+      (cst().root(Kind::Root, group().of_many0()) - end()).run(pc)
+    })
+    .with_rule(Kind::Group, |pc| group().run(pc))
+    .with_rule(Kind::List, |pc| list().run(pc))
+  }
+
+  fn reparse_sample(edit: TextEdit) -> (GreenNode<Kind>, Reparse<Kind>) {
+    let parser = parser();
+    let old_input = "[(a b) (c)] [(d)]".chars().collect::<Vec<_>>();
+    let old_tree = parser.parse(&old_input).unwrap();
+    let new_input = edit.apply(&old_input);
+    let reparse = parser.reparse(&old_tree, &edit, &new_input).unwrap();
+    assert_eq!(reparse.tree, parser.parse(&new_input).unwrap());
+    assert_eq!(reparse.tree.text(), String::from_iter(&new_input));
+    (old_tree, reparse)
+  }
+
+  fn group_node(tree: &GreenNode<Kind>, index: usize) -> GreenNode<Kind> {
+    match &tree.children()[index] {
+      GreenElement::Node(node) => node.clone(),
+      GreenElement::Token(_) => panic!("expected a group node"),
+    }
+  }
+
+  #[test]
+  fn test_reparse_innermost_rule() {
+    let (old_tree, reparse) = reparse_sample(TextEdit::new(4..4, "x y"));
+
+    assert_eq!(reparse.changed, 1..10);
+    assert!(group_node(&old_tree, 1).ptr_eq(&group_node(&reparse.tree, 1)));
+    assert!(!group_node(&old_tree, 0).ptr_eq(&group_node(&reparse.tree, 0)));
+  }
+
+  #[test]
+  fn test_reparse_falls_back_to_enclosing_rules() {
+    let (old_tree, reparse) = reparse_sample(TextEdit::new(5..8, " "));
+    assert_eq!(reparse.changed, 0..10);
+    assert!(group_node(&old_tree, 1).ptr_eq(&group_node(&reparse.tree, 1)));
+
+    let (_, reparse) = reparse_sample(TextEdit::new(10..13, ""));
+    assert_eq!(reparse.changed, 0..14);
+  }
+}
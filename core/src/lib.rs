@@ -2,21 +2,48 @@
 #![feature(type_alias_impl_trait)]
 #![feature(associated_type_defaults)]
 
+extern crate self as twill_core;
+
+mod abnf;
 mod combinators;
 mod committed_status;
+mod cst;
 mod element;
+mod general_category;
+mod incremental;
+mod parse;
 mod parse_context;
 mod parse_error;
 mod parse_result;
 mod parser;
+mod parser_macro;
+mod peg;
+mod syntax;
+mod token;
+mod tokenizer;
 pub mod util;
 
+#[doc(hidden)]
+pub use crate::parse::__boxed;
+#[doc(hidden)]
+pub use crate::parser_macro::__erase;
+
 pub mod prelude {
+  pub use crate::abnf::*;
   pub use crate::combinators::*;
   pub use crate::committed_status::*;
+  pub use crate::cst::*;
   pub use crate::element::*;
+  pub use crate::general_category::*;
+  pub use crate::incremental::*;
+  pub use crate::parse::*;
   pub use crate::parse_context::*;
   pub use crate::parse_error::*;
   pub use crate::parse_result::*;
   pub use crate::parser::*;
+  pub use crate::peg::*;
+  pub use crate::syntax::*;
+  pub use crate::token::*;
+  pub use crate::tokenizer::*;
+  pub use twill_derive::Parse;
 }
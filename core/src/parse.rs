@@ -0,0 +1,135 @@
+use crate::parser::{
+  AndThenParser, BoxedParserFn, CollectParser, ConversionParser, Parser, ParserRunner, RepeatParser,
+};
+use crate::prelude::{elm_pred_ref, Lexer};
+
+/// A type with a canonical `char` parser, usually derived with `#[derive(Parse)]`.
+///
+/// The derived parser parses the fields in declaration order and tries enum variants in declaration order. The
+/// `#[twill(...)]` attributes add tokens: `tag = "let"` before an item, `surround("(", ")")` around it, and on a
+/// `Vec` field `sep = ","` between its items. `with = path` uses the parser returned by `path()` for a field. Every
+/// token skips trailing whitespace, so leading whitespace of the input is left to the caller.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// #[derive(Debug, Clone, PartialEq, Parse)]
+/// enum Expr {
+///   #[twill(surround("[", "]"))]
+///   List(#[twill(sep = ",")] Vec<Expr>),
+///   Neg(#[twill(tag = "-")] Box<Expr>),
+///   Num(u32),
+/// }
+///
+/// #[derive(Debug, Clone, PartialEq, Parse)]
+/// #[twill(tag = "let")]
+/// struct Let {
+///   #[twill(with = ident)]
+///   name: String,
+///   #[twill(tag = "=")]
+///   value: Expr,
+/// }
+///
+/// fn ident<'a>() -> impl ParserRunner<'a, char, String> {
+///   Lexer::new().lexeme(elm_alpha_ref().of_many1().map(String::from_iter))
+/// }
+///
+/// let input = "let xs = [1, -2, [ ]]".chars().collect::<Vec<_>>();
+///
+/// let result = Let::parser().parse(&input);
+///
+/// assert_eq!(
+///   result.success().unwrap(),
+///   Let {
+///     name: "xs".to_string(),
+///     value: Expr::List(vec![Expr::Num(1), Expr::Neg(Box::new(Expr::Num(2))), Expr::List(vec![])]),
+///   }
+/// );
+/// ```
+pub trait Parse: Sized + Clone + 'static {
+  /// Returns the parser of this type.
+  fn parser<'a>() -> impl ParserRunner<'a, char, Self>;
+}
+
+macro_rules! impl_parse_for_integer {
+  ($signed:expr, $($t:ty),*) => {
+    $(
+      impl Parse for $t {
+        fn parser<'a>() -> impl ParserRunner<'a, char, Self> {
+          let sign = elm_pred_ref(|c: &char| $signed && *c == '-').repeat(0..=1);
+          let digits = elm_pred_ref(|c: &char| c.is_ascii_digit()).of_many1();
+          let number = sign.and_then(digits).collect().map_res(|cs| String::from_iter(cs).parse::<$t>());
+          Lexer::new().lexeme(number)
+        }
+      }
+    )*
+  };
+}
+
+impl_parse_for_integer!(true, i8, i16, i32, i64, i128, isize);
+impl_parse_for_integer!(false, u8, u16, u32, u64, u128, usize);
+
+/// Erases the type of a `char` parser, so that the parsers generated by `#[derive(Parse)]` stay flat.
+#[doc(hidden)]
+pub fn __boxed<'a, A, P>(parser: P) -> Parser<'a, char, A, BoxedParserFn<'a, char, A>>
+where
+  A: 'a,
+  P: ParserRunner<'a, char, A>, {
+  Parser::new(Box::new(move |parse_context| parser.run(parse_context)))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[derive(Debug, Clone, PartialEq, Parse)]
+  enum Stmt {
+    #[twill(tag = "print")]
+    Print(#[twill(sep = ",")] Vec<i64>),
+    #[twill(tag = "pass")]
+    Pass,
+    Block(Block),
+  }
+
+  #[derive(Debug, Clone, PartialEq, Parse)]
+  #[twill(surround("{", "}"))]
+  struct Block {
+    stmts: Vec<Stmt>,
+    #[twill(tag = "?")]
+    flag: Option<u8>,
+  }
+
+  #[test]
+  fn test_derive_parse_nested_types() {
+    let input = "{print 1,-2 {pass} ?7}".chars().collect::<Vec<_>>();
+
+    let result = Block::parser().parse(&input);
+
+    assert_eq!(
+      result.success().unwrap(),
+      Block {
+        stmts: vec![
+          Stmt::Print(vec![1, -2]),
+          Stmt::Block(Block {
+            stmts: vec![Stmt::Pass],
+            flag: None,
+          })
+        ],
+        flag: Some(7),
+      }
+    );
+  }
+
+  #[test]
+  fn test_derive_parse_keyword_tags() {
+    let (keyword, overflow) = (
+      "{ printx }".chars().collect::<Vec<_>>(),
+      "256".chars().collect::<Vec<_>>(),
+    );
+
+    assert!(Block::parser().parse(&keyword).is_failure());
+    assert!(u8::parser().parse(&overflow).is_failure());
+  }
+}
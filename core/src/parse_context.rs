@@ -5,12 +5,19 @@ use std::fmt::Debug;
 pub struct ParseContext<'a, I> {
   input: &'a [I],
   offset: usize,
+  // For bit-level input, the elements stand for the bits of these bytes
+  bytes: &'a [u8],
 }
 
 impl<'a, I> ParseContext<'a, I> {
   /// Create a new ParseContext
   pub fn new(input: &'a [I], offset: usize) -> Self {
-    Self { input, offset }
+    Self::with_bytes(input, &[], offset)
+  }
+
+  /// Create a new ParseContext whose input elements stand for the bits of `bytes`
+  pub(crate) fn with_bytes(input: &'a [I], bytes: &'a [u8], offset: usize) -> Self {
+    Self { input, offset, bytes }
   }
 
   /// Get the last offset if available
@@ -29,7 +36,7 @@ impl<'a, I> ParseContext<'a, I> {
 
   /// Create a new context by advancing n positions
   pub fn add_offset(&self, n: usize) -> ParseContext<'a, I> {
-    Self::new(self.input, self.offset + n)
+    Self::with_bytes(self.input, self.bytes, self.offset + n)
   }
 
   /// Get the remaining input slice
@@ -60,6 +67,11 @@ impl<'a, I> ParseContext<'a, I> {
     self.input
   }
 
+  /// Get the bytes behind a bit-level input, which are empty for other inputs
+  pub(crate) fn bytes(&self) -> &'a [u8] {
+    self.bytes
+  }
+
   /// Get total input length
   pub fn total_length(&self) -> usize {
     self.input.len()
@@ -79,8 +91,16 @@ impl<'a, I> ParseContext<'a, I> {
     }
   }
 
+  /// Create a new context at the same offset whose input ends `n` elements after the current position
+  ///
+  /// Offsets stay relative to the original input, so errors raised inside the bounded context keep their positions.
+  pub fn bounded(&self, n: usize) -> Self {
+    let end = (self.offset + n).min(self.input.len());
+    Self::with_bytes(&self.input[..end], self.bytes, self.offset)
+  }
+
   /// Create a new context with the same state (same input and offset)
   pub fn with_same_state(&self) -> Self {
-    Self::new(self.input, self.offset)
+    Self::with_bytes(self.input, self.bytes, self.offset)
   }
 }
@@ -20,6 +20,8 @@ pub enum ParseError<'a, I: 'a> {
   },
   /// 解析中断のエラー
   Incomplete,
+  /// 不足している要素数が分かっている解析中断のエラー
+  IncompleteNeeded { needed: usize },
   /// 期待から逸れた際のエラー
   Expect {
     offset: usize,
@@ -32,12 +34,37 @@ pub enum ParseError<'a, I: 'a> {
     inner: Option<Box<ParseError<'a, I>>>,
     message: String,
   },
+  /// 順不同パーサのフィールドが欠落・重複した際のエラー
+  Permutation {
+    offset: usize,
+    index: usize,
+    kind: PermutationErrorKind,
+  },
+}
+
+/// The kind of [ParseError::Permutation].
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq)]
+pub enum PermutationErrorKind {
+  /// A required field was not found.
+  Missing,
+  /// A field appeared more than once.
+  Duplicate,
+}
+
+impl Display for PermutationErrorKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PermutationErrorKind::Missing => write!(f, "Missing"),
+      PermutationErrorKind::Duplicate => write!(f, "Duplicate"),
+    }
+  }
 }
 
 impl<'a, I: 'a> Display for ParseError<'a, I> {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
       ParseError::Incomplete => write!(f, "Incomplete"),
+      ParseError::IncompleteNeeded { needed } => write!(f, "Incomplete: {} more elements needed", needed),
       ParseError::Mismatch {
         ref message,
         ref offset,
@@ -63,6 +90,11 @@ impl<'a, I: 'a> Display for ParseError<'a, I> {
         ref offset,
         inner: None,
       } => write!(f, "{} at {}", message, offset),
+      ParseError::Permutation {
+        ref offset,
+        ref index,
+        ref kind,
+      } => write!(f, "{} field #{} at {}", kind, index, offset),
     }
   }
 }
@@ -88,7 +120,7 @@ impl ParseError<'_, u8> {
 impl<'a, I: 'a> ParseError<'a, I> {
   pub fn input(&self) -> Option<&[I]> {
     match self {
-      ParseError::Incomplete => None,
+      ParseError::Incomplete | ParseError::IncompleteNeeded { .. } => None,
       ParseError::Mismatch {
         input, offset, length, ..
       } => Some(&input[*offset..(*offset + length)]),
@@ -100,6 +132,7 @@ impl<'a, I: 'a> ParseError<'a, I> {
         inner: Some(ref inner), ..
       } => inner.input(),
       ParseError::Custom { inner: None, .. } => None,
+      ParseError::Permutation { .. } => None,
     }
   }
 
@@ -119,8 +152,20 @@ impl<'a, I: 'a> ParseError<'a, I> {
     matches!(self, ParseError::Conversion { .. })
   }
 
+  pub fn is_permutation(&self) -> bool {
+    matches!(self, ParseError::Permutation { .. })
+  }
+
   pub fn is_in_complete(&self) -> bool {
-    matches!(self, ParseError::Incomplete)
+    matches!(self, ParseError::Incomplete | ParseError::IncompleteNeeded { .. })
+  }
+
+  /// Returns the number of elements needed to continue, if known.
+  pub fn needed(&self) -> Option<usize> {
+    match self {
+      ParseError::IncompleteNeeded { needed } => Some(*needed),
+      _ => None,
+    }
   }
 
   pub fn of_expect(offset: usize, inner: Box<ParseError<'a, I>>, message: String) -> Self {
@@ -149,7 +194,15 @@ impl<'a, I: 'a> ParseError<'a, I> {
     }
   }
 
+  pub fn of_permutation(offset: usize, index: usize, kind: PermutationErrorKind) -> Self {
+    ParseError::Permutation { offset, index, kind }
+  }
+
   pub fn of_in_complete() -> Self {
     ParseError::Incomplete
   }
+
+  pub fn of_in_complete_needed(needed: usize) -> Self {
+    ParseError::IncompleteNeeded { needed }
+  }
 }
@@ -42,6 +42,14 @@ pub trait ParserRunner<'a, I: 'a, A>: Clone + Sized + 'a {
   }
 }
 
+/// The function run by a [Parser], as a trait alias that keeps the signatures of parser constructors short.
+pub trait ParserFn<'a, I: 'a, A>: Fn(ParseContext<'a, I>) -> ParseResult<'a, I, A> + 'a {}
+
+impl<'a, I: 'a, A, F> ParserFn<'a, I, A> for F where F: Fn(ParseContext<'a, I>) -> ParseResult<'a, I, A> + 'a {}
+
+/// The boxed function of a [Parser] whose type is erased.
+pub type BoxedParserFn<'a, I, A> = Box<dyn Fn(ParseContext<'a, I>) -> ParseResult<'a, I, A> + 'a>;
+
 pub struct Parser<'a, I: 'a, A, F>
 where
   F: Fn(ParseContext<'a, I>) -> ParseResult<'a, I, A> + 'a, {
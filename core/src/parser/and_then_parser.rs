@@ -15,9 +15,8 @@ pub trait AndThenParser<'a, I: 'a, A>: ParserMonad<'a, I, A> {
     A: 'a,
     B: 'a,
     P2: ParserRunner<'a, I, B> + 'a, {
-    Parser::new(move |context| {
-      let original_input = context.original_input();
-      let initial_offset = context.next_offset();
+    Parser::new(move |context: ParseContext<'a, I>| {
+      let initial_context = context.with_same_state();
 
       match self.run(context) {
         ParseResult::Success {
@@ -25,8 +24,7 @@ pub trait AndThenParser<'a, I: 'a, A>: ParserMonad<'a, I, A> {
           length: length1,
           ..
         } => {
-          let new_offset = initial_offset + length1;
-          let new_context = ParseContext::new(original_input, new_offset);
+          let new_context = initial_context.add_offset(length1);
 
           match p2.run(new_context) {
             ParseResult::Success {
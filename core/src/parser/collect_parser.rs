@@ -1,7 +1,13 @@
 use crate::parse_context::ParseContext;
 use crate::parse_result::ParseResult;
-use crate::parser::{Parser, ParserRunner};
+use crate::parser::{Parser, ParserFn, ParserRunner};
 use std::fmt::Debug;
+use std::ops::Range;
+
+/// A parsed value together with the input slice it was parsed from.
+type WithSlice<'a, I, A> = (A, &'a [I]);
+/// A parsed value together with the range of offsets it was parsed from.
+type WithSpan<A> = (A, Range<usize>);
 
 pub trait CollectParser<'a, I: 'a, A>: ParserRunner<'a, I, A> + Sized
 where
@@ -11,19 +17,12 @@ where
     I: Debug + 'a,
     A: Debug + 'a, {
     Parser::new(move |parse_context| {
-      match self.run(parse_context) {
-        ParseResult::Success {
-          parse_context, length, ..
-        } => {
+      let start_offset = parse_context.next_offset();
+      match self.run(parse_context.with_same_state()) {
+        ParseResult::Success { length, .. } => {
           log::debug!("length: {}", length);
           log::debug!("parse_context: {:?}", parse_context);
-          // 元の入力に対して、適切な位置から消費した長さ分のスライスを取得
-          // オフセットが長さより小さい場合は、最初のオフセットは0
-          let start_offset = if parse_context.next_offset() >= length {
-            parse_context.next_offset() - length
-          } else {
-            0
-          };
+          // 実行前のオフセットから消費した長さ分のスライスを取得
           let slice = parse_context.slice_with_offset_len(start_offset, length);
           ParseResult::successful(parse_context, slice, length)
         }
@@ -35,6 +34,78 @@ where
       }
     })
   }
+
+  /// Returns a [Parser] that yields the parsed value together with the consumed input slice.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let text: &str = "x123";
+  /// let input = text.chars().collect::<Vec<_>>();
+  ///
+  /// let parser = elm('x') * elm_digit_ref().of_many1().map(|digits| digits.len()).recognize_with_value();
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), (3, &input[1..]));
+  /// ```
+  fn recognize_with_value(self) -> Parser<'a, I, WithSlice<'a, I, A>, impl ParserFn<'a, I, WithSlice<'a, I, A>>>
+  where
+    I: Debug + 'a,
+    A: 'a, {
+    Parser::new(move |parse_context| {
+      let start_offset = parse_context.next_offset();
+      match self.run(parse_context.with_same_state()) {
+        ParseResult::Success { value, length, .. } => {
+          let slice = parse_context.slice_with_offset_len(start_offset, length);
+          ParseResult::successful(parse_context, (value, slice), length)
+        }
+        ParseResult::Failure {
+          parse_context,
+          error,
+          committed_status,
+        } => ParseResult::failed(parse_context, error, committed_status),
+      }
+    })
+  }
+
+  /// Returns a [Parser] that yields the parsed value together with the range of offsets it was parsed from.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let text: &str = "(abc)";
+  /// let input = text.chars().collect::<Vec<_>>();
+  ///
+  /// let parser = surround(elm('('), tag("abc").with_span(), elm(')'));
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), ("abc".to_string(), 1..4));
+  /// ```
+  fn with_span(self) -> Parser<'a, I, WithSpan<A>, impl ParserFn<'a, I, WithSpan<A>>>
+  where
+    A: 'a, {
+    Parser::new(move |parse_context| {
+      let start_offset = parse_context.next_offset();
+      match self.run(parse_context.with_same_state()) {
+        ParseResult::Success { value, length, .. } => {
+          ParseResult::successful(parse_context, (value, start_offset..start_offset + length), length)
+        }
+        ParseResult::Failure {
+          parse_context,
+          error,
+          committed_status,
+        } => ParseResult::failed(parse_context, error, committed_status),
+      }
+    })
+  }
 }
 
 impl<'a, T, I: 'a, A> CollectParser<'a, I, A> for T where T: ParserRunner<'a, I, A> + 'a {}
@@ -73,4 +144,39 @@ mod tests {
 
     assert!(result.is_ok());
   }
+
+  #[test]
+  fn test_collect_after_mapped_parser() {
+    let text: &str = "abc";
+    let input = text.chars().collect::<Vec<_>>();
+    let p = elm_ref('a') * (elm_ref('b') + elm_ref('c')).map(|_| ()).collect();
+
+    let result = p.parse(&input).to_result();
+
+    assert_eq!(result.unwrap(), &input[1..]);
+  }
+
+  #[test]
+  fn test_recognize_with_value() {
+    let text: &str = "-12;";
+    let input = text.chars().collect::<Vec<_>>();
+    let number = (elm('-').opt() + elm_digit_ref().of_many1()).map(|(sign, digits)| (sign.is_some(), digits.len()));
+    let p = number.recognize_with_value() - elm(';');
+
+    let result = p.parse(&input).to_result();
+
+    assert_eq!(result.unwrap(), ((true, 2), &input[0..3]));
+  }
+
+  #[test]
+  fn test_with_span() {
+    let text: &str = "a bb";
+    let input = text.chars().collect::<Vec<_>>();
+    let word = elm_alpha_ref().of_many1().map(|cs| cs.len()).with_span();
+    let p = word.of_many1_sep(elm(' '));
+
+    let result = p.parse(&input).to_result();
+
+    assert_eq!(result.unwrap(), vec![(1, 0..1), (2, 2..4)]);
+  }
 }
@@ -2,6 +2,10 @@ use crate::prelude::*;
 use crate::util::{Bound, RangeArgument};
 use std::fmt::Debug;
 
+/// The items of a repetition together with the value of its terminator or trailing separator.
+type ItemsWith<A, B> = (Vec<A>, B);
+type ItemsWithTrailing<A, B> = (Vec<A>, Option<B>);
+
 pub trait RepeatParser<'a, I: 'a, A>: ParserRunner<'a, I, A>
 where
   Self: 'a, {
@@ -85,200 +89,727 @@ where
     A: 'a,
     B: 'a,
     P2: ParserRunner<'a, I, B> + 'a, {
-    let range_capture = range;
+    self.repeat_sep_fold(range, separator_opt, Vec::new, |mut items, value| {
+      items.push(value);
+      items
+    })
+  }
 
+  /// Repeats `self` like [RepeatParser::repeat_sep], folding each item into an accumulator instead of a `Vec`.
+  ///
+  /// - range: a range of the number of repetitions
+  /// - separator_opt: an optional separator parser
+  /// - init: a closure that creates the initial accumulator on every run
+  /// - f: a closure that folds an item into the accumulator
+  fn repeat_sep_fold<P2, B, R, S, G, F>(
+    self,
+    range: R,
+    separator_opt: Option<P2>,
+    init: G,
+    f: F,
+  ) -> Parser<'a, I, S, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, S> + 'a>
+  where
+    I: Debug,
+    R: RangeArgument<usize> + 'a,
+    A: 'a,
+    B: 'a,
+    S: 'a,
+    G: Fn() -> S + 'a,
+    F: Fn(S, A) -> S + 'a,
+    P2: ParserRunner<'a, I, B> + 'a, {
     Parser::new(move |parse_context| {
       let mut all_length = 0;
-      let mut items = vec![];
-      let range = &range_capture;
-
-      // 最初のパース
-      let first_result = self.run(parse_context.with_same_state());
-
-      match first_result {
-        ParseResult::Success {
-          parse_context: pc1,
-          value,
-          length,
-        } => {
-          println!("length:{}", length);
-          let mut current_parse_context = pc1.add_offset(length);
-          items.push(value);
-          all_length += length;
-
-          // メインループ
-          loop {
-            let should_break = match range.end() {
-              Bound::Included(&max_count) => items.len() >= max_count,
-              Bound::Excluded(&max_count) => items.len() + 1 >= max_count,
-              _ => false,
-            };
-            if should_break {
-              break;
-            }
+      let mut count = 0;
+      let mut acc = init();
+
+      loop {
+        if reached_max_count(&range, count) {
+          break;
+        }
 
-            // セパレーターのパース
-            let mut sep_success = true;
-            let mut sep_length = 0;
-
-            if let Some(ref sep) = separator_opt {
-              // 新しいパースコンテキストを生成（with_same_stateを使用）
-              let sep_parse_context = current_parse_context.with_same_state();
-              let sep_result = sep.run(sep_parse_context);
-
-              match sep_result {
-                ParseResult::Success {
-                  parse_context: pc2,
-                  length,
-                  ..
-                } => {
-                  current_parse_context = pc2.add_offset(length);
-                  all_length += length;
-                  sep_length = length;
-                }
-                _ => {
-                  sep_success = false;
-                }
+        // セパレーターのパース
+        let mut sep_length = 0;
+        if count > 0 {
+          if let Some(ref sep) = separator_opt {
+            match sep.run(parse_context.add_offset(all_length)) {
+              ParseResult::Success { length, .. } => {
+                sep_length = length;
               }
+              ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => break,
+              ParseResult::Failure {
+                parse_context,
+                error,
+                committed_status,
+              } => return ParseResult::failed(parse_context, error, committed_status),
             }
+          }
+        }
 
-            if !sep_success {
-              break;
-            }
+        // 次の要素をパース
+        match self.run(parse_context.add_offset(all_length + sep_length)) {
+          // 入力を消費しない繰り返しは終わらないので、最小回数に達していれば止める
+          ParseResult::Success { length, .. } if sep_length + length == 0 && reached_min_count(&range, count) => break,
+          ParseResult::Success { value, length, .. } => {
+            acc = f(acc, value);
+            count += 1;
+            all_length += sep_length + length;
+          }
+          ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => break,
+          ParseResult::Failure {
+            parse_context,
+            error,
+            committed_status,
+          } => return ParseResult::failed(parse_context, error, committed_status),
+        }
+      }
 
-            // 次の要素をパース - 新しいパースコンテキストを生成
-            let next_parse_context = current_parse_context.with_same_state();
-            let next_result = self.run(next_parse_context);
-
-            match next_result {
-              ParseResult::Success {
-                parse_context: pc3,
-                value,
-                length,
-              } => {
-                current_parse_context = pc3.add_offset(length);
-                items.push(value);
-                all_length += length;
-              }
-              _ => {
-                if sep_length > 0 {
-                  all_length -= sep_length;
-                }
-                break;
-              }
+      if let Some(pe) = min_count_error(&range, &parse_context, count, all_length) {
+        return ParseResult::failed_with_uncommitted(parse_context, pe);
+      }
+
+      ParseResult::successful(parse_context, acc, all_length)
+    })
+  }
+
+  /// Repeats `self` within `range`, folding each item into an accumulator starting from `init`.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let text: &str = "1234";
+  /// let input = text.chars().collect::<Vec<_>>();
+  ///
+  /// let parser = elm_digit_ref().repeat_fold(..=3, 0, |acc, c: &char| acc * 10 + c.to_digit(10).unwrap());
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), 123);
+  /// ```
+  fn repeat_fold<R, S, F>(
+    self,
+    range: R,
+    init: S,
+    f: F,
+  ) -> Parser<'a, I, S, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, S> + 'a>
+  where
+    I: Debug,
+    R: RangeArgument<usize> + 'a,
+    A: 'a,
+    S: Clone + 'a,
+    F: Fn(S, A) -> S + 'a, {
+    let none_separator: Option<Self> = None;
+    self.repeat_sep_fold(range, none_separator, move || init.clone(), f)
+  }
+
+  /// Repeats `self` zero or more times, folding each item into an accumulator starting from `init`.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let text: &str = "abc";
+  /// let input = text.chars().collect::<Vec<_>>();
+  ///
+  /// let parser = elm_any().fold0(0, |n, _| n + 1);
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), 3);
+  /// ```
+  fn fold0<S, F>(self, init: S, f: F) -> Parser<'a, I, S, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, S> + 'a>
+  where
+    I: Debug,
+    A: 'a,
+    S: Clone + 'a,
+    F: Fn(S, A) -> S + 'a, {
+    self.repeat_fold(0.., init, f)
+  }
+
+  /// Repeats `self` one or more times, folding each item into an accumulator starting from `init`.
+  fn fold1<S, F>(self, init: S, f: F) -> Parser<'a, I, S, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, S> + 'a>
+  where
+    I: Debug,
+    A: 'a,
+    S: Clone + 'a,
+    F: Fn(S, A) -> S + 'a, {
+    self.repeat_fold(1.., init, f)
+  }
+
+  /// Repeats `self` zero or more times, extending a collection created by [Default::default] with each item.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let text: &str = "abc1";
+  /// let input = text.chars().collect::<Vec<_>>();
+  ///
+  /// let parser = elm_alpha_ref().map(|c| *c).repeat_into::<String>();
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), "abc");
+  /// ```
+  fn repeat_into<C>(self) -> Parser<'a, I, C, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, C> + 'a>
+  where
+    I: Debug,
+    A: 'a,
+    C: Extend<A> + Default + 'a, {
+    self.repeat_into_range(0..)
+  }
+
+  /// Repeats `self` within `range`, extending a collection created by [Default::default] with each item.
+  fn repeat_into_range<C, R>(
+    self,
+    range: R,
+  ) -> Parser<'a, I, C, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, C> + 'a>
+  where
+    I: Debug,
+    R: RangeArgument<usize> + 'a,
+    A: 'a,
+    C: Extend<A> + Default + 'a, {
+    let none_separator: Option<Self> = None;
+    self.repeat_sep_fold(range, none_separator, C::default, |mut collection, value| {
+      collection.extend(Some(value));
+      collection
+    })
+  }
+
+  /// Repeats `self` until `end` succeeds, returning the items together with the value of `end`.
+  ///
+  /// `end` is tried before every item once the lower bound of `range` is reached, so the items never swallow the
+  /// terminator.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let text: &str = "aa;";
+  /// let input = text.chars().collect::<Vec<_>>();
+  ///
+  /// let parser = elm('a').repeat_till(1..=2, elm(';'));
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), (vec!['a', 'a'], ';'));
+  /// ```
+  fn repeat_till<P2, B, R>(
+    self,
+    range: R,
+    end: P2,
+  ) -> Parser<'a, I, ItemsWith<A, B>, impl ParserFn<'a, I, ItemsWith<A, B>>>
+  where
+    I: Debug,
+    R: RangeArgument<usize> + 'a,
+    A: 'a,
+    B: 'a,
+    P2: ParserRunner<'a, I, B> + 'a, {
+    Parser::new(move |parse_context| {
+      let mut all_length = 0;
+      let mut items = vec![];
+
+      loop {
+        let pc = parse_context.add_offset(all_length);
+
+        if min_count_error(&range, &parse_context, items.len(), all_length).is_none() {
+          match end.run(pc.with_same_state()) {
+            ParseResult::Success { value, length, .. } => {
+              return ParseResult::successful(parse_context, (items, value), all_length + length);
             }
+            ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => {}
+            ParseResult::Failure {
+              parse_context,
+              error,
+              committed_status,
+            } => return ParseResult::failed(parse_context, error, committed_status),
           }
+        }
 
-          if let Bound::Included(&min_count) = range.start() {
-            if items.len() < min_count {
-              let pc = parse_context.add_offset(all_length);
-              let input = pc.input();
-              let offset = pc.next_offset();
-              let pe = ParseError::of_mismatch(
-                input,
-                offset,
-                all_length,
-                format!(
-                  "expect repeat at least {} times, found {} times",
-                  min_count,
-                  items.len()
-                ),
-              );
-              return ParseResult::failed(pc, pe, CommittedStatus::Uncommitted);
-            }
+        if reached_max_count(&range, items.len()) {
+          let msg = format!("expect terminator after {} times", items.len());
+          let pe = ParseError::of_mismatch(pc.original_input(), pc.next_offset(), 0, msg);
+          return ParseResult::failed(parse_context, pe, (all_length != 0).into());
+        }
+
+        match self.run(pc.with_same_state()) {
+          ParseResult::Success { length: 0, .. } if reached_min_count(&range, items.len()) => {
+            let msg = format!("expect terminator, found an empty item after {} times", items.len());
+            let pe = ParseError::of_mismatch(pc.original_input(), pc.next_offset(), 0, msg);
+            return ParseResult::failed(parse_context, pe, (all_length != 0).into());
           }
+          ParseResult::Success { value, length, .. } => {
+            items.push(value);
+            all_length += length;
+          }
+          ParseResult::Failure {
+            parse_context,
+            error,
+            committed_status,
+          } => return ParseResult::failed(parse_context, error, committed_status.or(&(all_length != 0).into())),
+        }
+      }
+    })
+  }
 
-          ParseResult::successful(parse_context, items, all_length)
+  /// Repeats `self` zero or more times until `end` succeeds, returning the items together with the value of `end`.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let text: &str = "abc*/";
+  /// let input = text.chars().collect::<Vec<_>>();
+  ///
+  /// let parser = elm_any().many_till(tag("*/"));
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.success().unwrap(), (vec!['a', 'b', 'c'], "*/".to_string()));
+  /// ```
+  fn many_till<P2, B>(self, end: P2) -> Parser<'a, I, ItemsWith<A, B>, impl ParserFn<'a, I, ItemsWith<A, B>>>
+  where
+    I: Debug,
+    A: 'a,
+    B: 'a,
+    P2: ParserRunner<'a, I, B> + 'a, {
+    self.repeat_till(0.., end)
+  }
+
+  /// Parses items separated by `separator`, allowing one optional trailing separator.
+  ///
+  /// Returns the items and the value of the trailing separator, if any.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let text: &str = "a,a,";
+  /// let input = text.chars().collect::<Vec<_>>();
+  ///
+  /// let parser = elm('a').sep_by_with_trailing(1.., elm(','));
+  ///
+  /// let result = parser.parse(&input);
+  ///
+  /// assert!(result.is_success());
+  /// assert_eq!(result.consumed_count(), 4);
+  /// assert_eq!(result.success().unwrap(), (vec!['a', 'a'], Some(',')));
+  /// ```
+  fn sep_by_with_trailing<P2, B, R>(
+    self,
+    range: R,
+    separator: P2,
+  ) -> Parser<'a, I, ItemsWithTrailing<A, B>, impl ParserFn<'a, I, ItemsWithTrailing<A, B>>>
+  where
+    I: Debug,
+    R: RangeArgument<usize> + 'a,
+    A: 'a,
+    B: 'a,
+    P2: ParserRunner<'a, I, B> + 'a, {
+    Parser::new(move |parse_context| {
+      let mut all_length = 0;
+      let mut items = vec![];
+      let mut trailing = None;
+
+      let mut sep_length = 0;
+
+      loop {
+        if reached_max_count(&range, items.len()) {
+          break;
+        }
+
+        match self.run(parse_context.add_offset(all_length)) {
+          ParseResult::Success { length, .. } if sep_length + length == 0 && reached_min_count(&range, items.len()) => {
+            break
+          }
+          ParseResult::Success { value, length, .. } => {
+            items.push(value);
+            all_length += length;
+            trailing = None;
+          }
+          ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => break,
+          ParseResult::Failure {
+            parse_context,
+            error,
+            committed_status,
+          } => return ParseResult::failed(parse_context, error, committed_status),
         }
-        ParseResult::Failure {
-          parse_context,
-          error,
-          committed_status,
-        } => ParseResult::failed(parse_context, error, committed_status),
+
+        // 最大回数に達したら、後続のセパレーターは消費しない
+        if reached_max_count(&range, items.len()) {
+          break;
+        }
+
+        match separator.run(parse_context.add_offset(all_length)) {
+          ParseResult::Success { value, length, .. } => {
+            all_length += length;
+            sep_length = length;
+            trailing = Some(value);
+          }
+          ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => break,
+          ParseResult::Failure {
+            parse_context,
+            error,
+            committed_status,
+          } => return ParseResult::failed(parse_context, error, committed_status),
+        }
+      }
+
+      if let Some(pe) = min_count_error(&range, &parse_context, items.len(), all_length) {
+        return ParseResult::failed_with_uncommitted(parse_context, pe);
       }
+
+      ParseResult::successful(parse_context, (items, trailing), all_length)
     })
   }
+
+  /// Parses zero or more items separated and optionally terminated by `separator`.
+  fn sep_end_by0<P2, B>(self, separator: P2) -> Parser<'a, I, Vec<A>, impl ParserFn<'a, I, Vec<A>>>
+  where
+    I: Debug,
+    A: 'a,
+    B: 'a,
+    P2: ParserRunner<'a, I, B> + 'a, {
+    let parser = self.sep_by_with_trailing(0.., separator);
+    Parser::new(move |parse_context| parser.run(parse_context).map(|(items, _)| items))
+  }
+
+  /// Parses one or more items separated and optionally terminated by `separator`.
+  fn sep_end_by1<P2, B>(self, separator: P2) -> Parser<'a, I, Vec<A>, impl ParserFn<'a, I, Vec<A>>>
+  where
+    I: Debug,
+    A: 'a,
+    B: 'a,
+    P2: ParserRunner<'a, I, B> + 'a, {
+    let parser = self.sep_by_with_trailing(1.., separator);
+    Parser::new(move |parse_context| parser.run(parse_context).map(|(items, _)| items))
+  }
+}
+
+fn reached_max_count<R: RangeArgument<usize>>(range: &R, count: usize) -> bool {
+  match range.end() {
+    Bound::Included(&max_count) => count >= max_count,
+    Bound::Excluded(&max_count) => count + 1 >= max_count,
+    Bound::Unbounded => false,
+  }
+}
+
+fn reached_min_count<R: RangeArgument<usize>>(range: &R, count: usize) -> bool {
+  !matches!(range.start(), Bound::Included(&min_count) if count < min_count)
+}
+
+fn min_count_error<'a, I, R: RangeArgument<usize>>(
+  range: &R,
+  parse_context: &ParseContext<'a, I>,
+  count: usize,
+  length: usize,
+) -> Option<ParseError<'a, I>> {
+  match range.start() {
+    Bound::Included(&min_count) if count < min_count => Some(ParseError::of_mismatch(
+      parse_context.original_input(),
+      parse_context.next_offset(),
+      length,
+      format!("expect repeat at least {} times, found {} times", min_count, count),
+    )),
+    _ => None,
+  }
 }
 
 impl<'a, T, I: 'a, A> RepeatParser<'a, I, A> for T where T: ParserRunner<'a, I, A> + ParserMonad<'a, I, A> {}
 
-// #[cfg(test)]
-// mod tests {
-//   use super::*;
-//
-//   #[test]
-//   fn test_basic_repeat() {
-//     // テストデータ
-//     let text = "aaab";
-//     let input: Vec<char> = text.chars().collect();
-//
-//     // 文字「a」を認識するクローン可能なパーサー
-//     let a_parser = elm_ref('a');
-//
-//     // 0回以上の繰り返し
-//     let many_a = a_parser.of_many0();
-//
-//     // パース実行
-//     let result = many_a.parse(&input);
-//
-//     // 結果を検証
-//     assert!(result.is_success());
-//     if let ParseResult::Success { value, length, .. } = result {
-//       assert_eq!(value.len(), 3); // 「a」が3回見つかるはず
-//       assert_eq!(length, 3); // 消費される長さは3
-//     } else {
-//       panic!("Expected success but got failure");
-//     }
-//   }
-//
-//   #[test]
-//   fn test_repeat_with_separator() {
-//     // テスト用の入力文字列
-//     let text = "a,a,a,b";
-//     let input: Vec<char> = text.chars().collect();
-//
-//     // 文字認識用パーサー - クローン可能なものを使用
-//     let a_parser = elm_ref('a');
-//     let comma_parser = elm_ref(',');
-//
-//     // カンマ区切りのリスト
-//     let a_comma_list = a_parser.of_many1_sep(comma_parser);
-//
-//     // パース実行
-//     let result = a_comma_list.parse(&input);
-//
-//     // 結果を検証
-//     assert!(result.is_success());
-//     if let ParseResult::Success { value, length, .. } = result {
-//       assert_eq!(value.len(), 3); // 「a」が3つあるはず
-//       assert_eq!(length, 5); // 「a,a,a」で長さ5
-//     } else {
-//       panic!("Expected success but got failure");
-//     }
-//   }
-//
-//   #[test]
-//   fn test_exact_repeat_count() {
-//     // テスト用の入力文字列
-//     let text = "aaaa";
-//     let input: Vec<char> = text.chars().collect();
-//
-//     // 文字「a」を認識するクローン可能なパーサー
-//     let a_parser = elm_ref('a');
-//
-//     // ちょうど3回の繰り返し
-//     let exactly_three_a = a_parser.count(3);
-//
-//     // パース実行
-//     let result = exactly_three_a.parse(&input);
-//
-//     // 結果を検証
-//     assert!(result.is_success());
-//     if let ParseResult::Success { value, length, .. } = result {
-//       assert_eq!(value.len(), 3); // 要素数は3
-//       assert_eq!(length, 3); // 消費された長さも3
-//     } else {
-//       panic!("Expected success but got failure");
-//     }
-//   }
-// }
+/// Parses items between `open` and `close`, separated and optionally terminated by `sep`.
+///
+/// Returns the items together with the value of `close`.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let text: &str = "[a,b,]";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let parser = delimited_list(elm('['), elm_alpha_ref(), elm(','), elm(']'));
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), (vec![&'a', &'b'], ']'));
+/// ```
+pub fn delimited_list<'a, I: Debug + 'a, A, B, C, D, P1, P2, P3, P4>(
+  open: P1,
+  item: P2,
+  sep: P3,
+  close: P4,
+) -> Parser<'a, I, ItemsWith<B, D>, impl ParserFn<'a, I, ItemsWith<B, D>>>
+where
+  A: 'a,
+  B: 'a,
+  C: 'a,
+  D: 'a,
+  P1: ParserRunner<'a, I, A> + 'a,
+  P2: ParserRunner<'a, I, B> + 'a,
+  P3: ParserRunner<'a, I, C> + 'a,
+  P4: ParserRunner<'a, I, D> + 'a, {
+  let rest = item.sep_end_by0(sep).and_then(close);
+  Parser::new(
+    move |parse_context: ParseContext<'a, I>| match open.run(parse_context.with_same_state()) {
+      ParseResult::Success { length, .. } => rest
+        .run(parse_context.add_offset(length))
+        .with_committed_fallback(length != 0)
+        .with_add_length(length),
+      ParseResult::Failure {
+        parse_context,
+        error,
+        committed_status,
+      } => ParseResult::failed(parse_context, error, committed_status),
+    },
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_basic_repeat() {
+    // テストデータ
+    let text = "aaab";
+    let input: Vec<char> = text.chars().collect();
+
+    // 文字「a」を認識するクローン可能なパーサー
+    let a_parser = elm_ref('a');
+
+    // 0回以上の繰り返し
+    let many_a = a_parser.of_many0();
+
+    // パース実行
+    let result = many_a.parse(&input);
+
+    // 結果を検証
+    assert!(result.is_success());
+    if let ParseResult::Success { value, length, .. } = result {
+      assert_eq!(value.len(), 3); // 「a」が3回見つかるはず
+      assert_eq!(length, 3); // 消費される長さは3
+    } else {
+      panic!("Expected success but got failure");
+    }
+  }
+
+  #[test]
+  fn test_repeat_with_separator() {
+    // テスト用の入力文字列
+    let text = "a,a,a,b";
+    let input: Vec<char> = text.chars().collect();
+
+    // 文字認識用パーサー - クローン可能なものを使用
+    let a_parser = elm_ref('a');
+    let comma_parser = elm_ref(',');
+
+    // カンマ区切りのリスト
+    let a_comma_list = a_parser.of_many1_sep(comma_parser);
+
+    // パース実行
+    let result = a_comma_list.parse(&input);
+
+    // 結果を検証
+    assert!(result.is_success());
+    if let ParseResult::Success { value, length, .. } = result {
+      assert_eq!(value.len(), 3); // 「a」が3つあるはず
+      assert_eq!(length, 5); // 「a,a,a」で長さ5
+    } else {
+      panic!("Expected success but got failure");
+    }
+  }
+
+  #[test]
+  fn test_exact_repeat_count() {
+    // テスト用の入力文字列
+    let text = "aaaa";
+    let input: Vec<char> = text.chars().collect();
+
+    // 文字「a」を認識するクローン可能なパーサー
+    let a_parser = elm_ref('a');
+
+    // ちょうど3回の繰り返し
+    let exactly_three_a = a_parser.count(3);
+
+    // パース実行
+    let result = exactly_three_a.parse(&input);
+
+    // 結果を検証
+    assert!(result.is_success());
+    if let ParseResult::Success { value, length, .. } = result {
+      assert_eq!(value.len(), 3); // 要素数は3
+      assert_eq!(length, 3); // 消費された長さも3
+    } else {
+      panic!("Expected success but got failure");
+    }
+  }
+
+  #[test]
+  fn test_many0_empty() {
+    let text = "b";
+    let input: Vec<char> = text.chars().collect();
+
+    let result = elm_ref('a').of_many0().parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 0);
+    assert!(result.success().unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_many1_with_mapped_item() {
+    let text = "aaab";
+    let input: Vec<char> = text.chars().collect();
+
+    let result = elm_ref('a').map(|c| *c).of_many1().parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 3);
+    assert_eq!(result.success().unwrap(), vec!['a', 'a', 'a']);
+  }
+
+  #[test]
+  fn test_fold() {
+    let text = "1,2,3";
+    let input: Vec<char> = text.chars().collect();
+
+    let digit = elm_digit_ref().map(|c: &char| c.to_digit(10).unwrap());
+    let result = digit
+      .clone()
+      .repeat_sep_fold(0.., Some(elm(',')), || 0, |acc, n| acc + n)
+      .parse(&input);
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 5);
+    assert_eq!(result.success().unwrap(), 6);
+
+    let result = digit.clone().fold1(0, |acc, n| acc + n).parse(&input[1..]);
+    assert!(result.is_failure());
+
+    let result = digit.fold0(0, |acc, n| acc + n).parse(&input[1..]);
+    assert!(result.is_success());
+    assert_eq!(result.success().unwrap(), 0);
+  }
+
+  #[test]
+  fn test_repeat_into() {
+    use std::collections::HashSet;
+
+    let text = "abcab;";
+    let input: Vec<char> = text.chars().collect();
+
+    let result = elm_alpha_ref().map(|c| *c).repeat_into::<HashSet<char>>().parse(&input);
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 5);
+    assert_eq!(result.success().unwrap().len(), 3);
+
+    let result = elm_alpha_ref()
+      .map(|c| *c)
+      .repeat_into_range::<String, _>(6..)
+      .parse(&input);
+    assert!(result.is_failure());
+  }
+
+  #[test]
+  fn test_many_till() {
+    let text = "ab-->c";
+    let input: Vec<char> = text.chars().collect();
+
+    let result = elm_any().many_till(tag("-->")).parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 5);
+    assert_eq!(result.success().unwrap(), (vec!['a', 'b'], "-->".to_string()));
+  }
+
+  #[test]
+  fn test_repeat_till_failure() {
+    let text = "aaa;";
+    let input: Vec<char> = text.chars().collect();
+
+    let result = elm('a').repeat_till(..=2, elm(';')).parse(&input);
+    assert!(result.is_failure());
+
+    let result = elm('a').repeat_till(4.., elm(';')).parse(&input);
+    assert!(result.is_failure());
+  }
+
+  #[test]
+  fn test_sep_end_by() {
+    let input: Vec<char> = "a,a,a,".chars().collect();
+    let result = elm('a').sep_end_by0(elm(',')).parse(&input);
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 6);
+    assert_eq!(result.success().unwrap().len(), 3);
+
+    let input: Vec<char> = "a,a".chars().collect();
+    let result = elm('a').sep_end_by1(elm(',')).parse(&input);
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 3);
+
+    let input: Vec<char> = "".chars().collect();
+    assert!(elm('a').sep_end_by0(elm(',')).parse(&input).is_success());
+    assert!(elm('a').sep_end_by1(elm(',')).parse(&input).is_failure());
+  }
+
+  #[test]
+  fn test_sep_by_with_trailing_max_count() {
+    let input: Vec<char> = "a,a,a,".chars().collect();
+    let result = elm('a').sep_by_with_trailing(..=2, elm(',')).parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 3);
+    assert_eq!(result.success().unwrap(), (vec!['a', 'a'], None));
+  }
+
+  #[test]
+  fn test_sep_end_by_without_clone() {
+    #[derive(Debug, PartialEq)]
+    struct Item(char);
+    let input: Vec<char> = "(a,b,)".chars().collect();
+    let item = || elm_alpha_ref().map_res(|c| Ok::<_, ()>(Item(*c)));
+
+    let result = delimited_list(elm('('), item(), elm(','), elm(')')).parse(&input);
+    assert_eq!(result.success().unwrap(), (vec![Item('a'), Item('b')], ')'));
+    assert_eq!(item().sep_end_by1(elm(',')).parse(&input[1..]).consumed_count(), 4);
+  }
+
+  #[test]
+  fn test_repeat_stops_on_empty_items() {
+    let input: Vec<char> = "ab".chars().collect();
+    let empty = || elm('a').opt().map(|_| ());
+
+    let result = empty().of_many0().parse(&input[1..]);
+    assert!(result.is_success());
+    assert_eq!(result.success().unwrap().len(), 0);
+    assert_eq!(empty().count(3).parse(&input[1..]).success().unwrap().len(), 3);
+    assert_eq!(empty().of_many1_sep(elm(',')).parse(&input).consumed_count(), 1);
+    assert_eq!(empty().sep_end_by0(elm(',')).parse(&input).consumed_count(), 1);
+    assert!(empty().many_till(elm(';')).parse(&input).is_failure());
+  }
+
+  #[test]
+  fn test_delimited_list() {
+    let input: Vec<char> = "(a, b)".chars().collect();
+    let parser = delimited_list(elm('('), elm_alpha_ref().map(|c| *c), tag(", "), elm(')'));
+
+    let result = parser.parse(&input);
+
+    assert!(result.is_success());
+    assert_eq!(result.consumed_count(), 6);
+    assert_eq!(result.success().unwrap(), (vec!['a', 'b'], ')'));
+  }
+}
@@ -37,6 +37,21 @@ where
       },
     )
   }
+
+  /// Lookahead parser - succeeds with the result of self without consuming input, fails uncommitted when self fails
+  fn peek(self) -> Parser<'a, I, A, impl Fn(ParseContext<'a, I>) -> ParseResult<'a, I, A> + 'a>
+  where
+    Self: Sized,
+    A: 'a, {
+    Parser::new(
+      move |parse_context: ParseContext<'a, I>| match self.run(parse_context) {
+        ParseResult::Success {
+          parse_context, value, ..
+        } => ParseResult::successful(parse_context, value, 0),
+        pr @ ParseResult::Failure { .. } => pr.with_uncommitted(),
+      },
+    )
+  }
 }
 
 /// Implement TransformParser for all types that implement Parser and ParserMonad
@@ -0,0 +1,202 @@
+use crate::parse_result::ParseResult;
+use crate::parser::{BoxedParserFn, Parser, ParserRunner};
+
+/// Defines `char` parsers with a PEG-like notation.
+///
+/// Each `rule name = expr;` expands to a function `fn name<'a>() -> Parser<'a, char, String, ...>` that returns the
+/// text matched by `expr`. With `rule name -> T = expr => f;` the text is converted by `f: Fn(String) -> Result<T, E>`,
+/// as with [map_res]. Errors of a rule are wrapped by [name] with the rule name, and rules may refer to each other
+/// recursively. Attributes and a visibility may precede `rule`.
+///
+/// Expressions are built from
+///
+/// - `"text"`: the literal text, as [tag]
+/// - `['a'..='z' | '_']`: a character matching the pattern
+/// - `name`: the rule `name`, or any function returning a `char` parser
+/// - `{ expr }`: a Rust expression of a `char` parser
+/// - `(e)`: grouping
+/// - `e?`, `e*`, `e+`: option and repetitions, as [opt], [of_many0] and [of_many1]
+/// - `&e`, `!e`: positive and negative lookahead, as [peek] and [not]
+/// - `e1 e2`: sequence
+/// - `e1 / e2`: ordered choice, which backtracks to `e2` whenever `e1` fails
+///
+/// [map_res]: crate::prelude::ConversionParser::map_res
+/// [name]: crate::prelude::LoggingParser::name
+/// [tag]: crate::prelude::tag
+/// [opt]: crate::prelude::OptParser::opt
+/// [of_many0]: crate::prelude::RepeatParser::of_many0
+/// [of_many1]: crate::prelude::RepeatParser::of_many1
+/// [peek]: crate::prelude::TransformParser::peek
+/// [not]: crate::prelude::TransformParser::not
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// use twill_core::parser;
+///
+/// parser! {
+///   rule digit = ['0'..='9'];
+///   /// A decimal number.
+///   pub rule number -> f64 = "-"? digit+ ("." digit+)? => |s| s.parse();
+///   rule numbers -> Vec<f64> = number ("," number)* => |s| s.split(',').map(|n| n.parse::<f64>()).collect();
+/// }
+///
+/// let input = "-1.5,2".chars().collect::<Vec<_>>();
+///
+/// assert_eq!(number().parse(&input).success().unwrap(), -1.5);
+/// assert_eq!(numbers().parse(&input).success().unwrap(), vec![-1.5, 2.0]);
+/// ```
+#[macro_export]
+macro_rules! parser {
+  () => {};
+  (@rule $header:tt [$($body:tt)*] => $action:expr; $($rest:tt)*) => {
+    $crate::parser!(@emit $header [$($body)*] $action);
+    $crate::parser!($($rest)*);
+  };
+  (@rule $header:tt [$($body:tt)*] ; $($rest:tt)*) => {
+    $crate::parser!(@emit $header [$($body)*]);
+    $crate::parser!($($rest)*);
+  };
+  (@rule $header:tt [$($body:tt)*] $t:tt $($rest:tt)*) => {
+    $crate::parser!(@rule $header [$($body)* $t] $($rest)*);
+  };
+  (@emit [[$($meta:tt)*] [$($vis:tt)*] $name:ident $ty:ty] [$($body:tt)*] $($action:expr)?) => {
+    $($meta)*
+    $($vis)* fn $name<'a>() -> $crate::prelude::Parser<'a, char, $ty, impl $crate::prelude::ParserFn<'a, char, $ty>> {
+      #[allow(unused_imports)]
+      use $crate::prelude::{
+        AttemptParser as _, CollectParser as _, ConversionParser as _, LoggingParser as _, OptParser as _,
+        OrParser as _, ParserMonad as _, ParserRunner as _, RepeatParser as _, TransformParser as _,
+      };
+      $crate::prelude::Parser::new(move |parse_context: $crate::prelude::ParseContext<'a, char>| {
+        $crate::parser!(@or [] [] $($body)*)
+          .collect()
+          .map(String::from_iter)
+          $(.map_res($action))?
+          .name(stringify!($name))
+          .run(parse_context)
+      })
+    }
+  };
+  (@or [$($alts:tt)*] [$($seq:tt)*] / $($rest:tt)*) => {
+    $crate::parser!(@or [$($alts)* [$($seq)*]] [] $($rest)*)
+  };
+  (@or [$($alts:tt)*] [$($seq:tt)*] $t:tt $($rest:tt)*) => {
+    $crate::parser!(@or [$($alts)*] [$($seq)* $t] $($rest)*)
+  };
+  (@or [$($alts:tt)*] [$($seq:tt)*]) => {
+    $crate::parser!(@choice $($alts)* [$($seq)*])
+  };
+  (@choice [$($seq:tt)*]) => {
+    $crate::parser!(@seq [] $($seq)*)
+  };
+  (@choice [$($seq:tt)*] $($alts:tt)+) => {
+    $crate::__erase($crate::parser!(@seq [] $($seq)*).attempt().or($crate::parser!(@choice $($alts)+)))
+  };
+  (@seq [$($items:tt)*] & $($rest:tt)+) => {
+    $crate::parser!(@suffix [$($items)*] [peek] $($rest)+)
+  };
+  (@seq [$($items:tt)*] ! $($rest:tt)+) => {
+    $crate::parser!(@suffix [$($items)*] [not] $($rest)+)
+  };
+  (@seq [$($items:tt)*] $($rest:tt)+) => {
+    $crate::parser!(@suffix [$($items)*] [] $($rest)+)
+  };
+  (@seq [$($items:tt)*]) => {
+    $crate::parser!(@join $($items)*)
+  };
+  (@suffix [$($items:tt)*] [$($prefix:ident)?] $atom:tt ? $($rest:tt)*) => {
+    $crate::parser!(@seq [$($items)* [$crate::__erase($crate::parser!(@atom $atom).attempt().opt() $(.$prefix())?)]] $($rest)*)
+  };
+  (@suffix [$($items:tt)*] [$($prefix:ident)?] $atom:tt * $($rest:tt)*) => {
+    $crate::parser!(@seq [$($items)* [$crate::__erase($crate::parser!(@atom $atom).attempt().of_many0() $(.$prefix())?)]] $($rest)*)
+  };
+  (@suffix [$($items:tt)*] [$($prefix:ident)?] $atom:tt + $($rest:tt)*) => {
+    $crate::parser!(@seq [$($items)* [$crate::__erase($crate::parser!(@atom $atom).attempt().of_many1() $(.$prefix())?)]] $($rest)*)
+  };
+  (@suffix [$($items:tt)*] [$($prefix:ident)?] $atom:tt $($rest:tt)*) => {
+    $crate::parser!(@seq [$($items)* [$crate::__erase($crate::parser!(@atom $atom) $(.$prefix())?)]] $($rest)*)
+  };
+  (@join) => {
+    $crate::__erase($crate::prelude::unit())
+  };
+  (@join [$($first:tt)*] $([$($items:tt)*])*) => {
+    $crate::__erase($($first)* $(* $($items)*)*)
+  };
+  (@atom $literal:literal) => {
+    $crate::__erase($crate::prelude::tag($literal))
+  };
+  (@atom [$($pattern:tt)*]) => {
+    $crate::__erase($crate::prelude::elm_pred_ref(|c: &char| matches!(*c, $($pattern)*)))
+  };
+  (@atom ($($expr:tt)*)) => {
+    $crate::parser!(@or [] [] $($expr)*)
+  };
+  (@atom {$($expr:tt)*}) => {
+    $crate::__erase({$($expr)*})
+  };
+  (@atom $rule:ident) => {
+    $crate::__erase($rule())
+  };
+  ($(#[$meta:meta])* $vis:vis rule $name:ident -> $ty:ty = $($rest:tt)*) => {
+    $crate::parser!(@rule [[$(#[$meta])*] [$vis] $name $ty] [] $($rest)*);
+  };
+  ($(#[$meta:meta])* $vis:vis rule $name:ident = $($rest:tt)*) => {
+    $crate::parser!(@rule [[$(#[$meta])*] [$vis] $name String] [] $($rest)*);
+  };
+}
+
+/// Discards the value of a `char` parser and erases its type, so that the parsers expanded by [parser!] stay flat.
+#[doc(hidden)]
+pub fn __erase<'a, A, P>(parser: P) -> Parser<'a, char, (), BoxedParserFn<'a, char, ()>>
+where
+  A: 'a,
+  P: ParserRunner<'a, char, A>, {
+  Parser::new(Box::new(move |parse_context| match parser.run(parse_context) {
+    ParseResult::Success {
+      parse_context, length, ..
+    } => ParseResult::successful(parse_context, (), length),
+    ParseResult::Failure {
+      parse_context,
+      error,
+      committed_status,
+    } => ParseResult::failed(parse_context, error, committed_status),
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  crate::parser! {
+    rule ws = [' ' | '\t']*;
+    rule ident = !keyword ['a'..='z' | '_'] ['a'..='z' | '0'..='9' | '_']*;
+    rule keyword = ("if" / "in") !['a'..='z'];
+    rule expr = term (ws ("+" / "-") ws term)*;
+    rule term = ident / { elm_digit_ref() }+ / "(" ws expr ws ")";
+    rule count -> usize = expr &")" => |s| Ok::<_, ()>(s.len());
+  }
+
+  #[test]
+  fn test_parser_macro_recursive_rules() {
+    let input = "(a + 12 - (b_1))".chars().collect::<Vec<_>>();
+
+    let result = (expr() - end()).parse(&input);
+
+    assert_eq!(result.success().unwrap(), "(a + 12 - (b_1))");
+    assert_eq!(count().parse(&input[1..]).success().unwrap(), 14);
+    let (keyword, identifier) = ("if".chars().collect::<Vec<_>>(), "iffy".chars().collect::<Vec<_>>());
+    assert!(ident().parse(&keyword).is_failure());
+    assert_eq!(ident().parse(&identifier).success().unwrap(), "iffy");
+  }
+
+  #[test]
+  fn test_parser_macro_names_rule_in_errors() {
+    let input = "(a + )".chars().collect::<Vec<_>>();
+
+    let error = term().parse(&input).failure().unwrap();
+
+    assert!(error.to_string().contains("failed to parse term"), "{}", error);
+  }
+}
@@ -0,0 +1,656 @@
+use crate::parse_context::ParseContext;
+use crate::parse_error::ParseError;
+use crate::parse_result::ParseResult;
+use crate::parser::*;
+use crate::prelude::{elm_pred_ref, unit};
+use crate::util::{parse_class_char, CharSet, Set};
+use std::cell::OnceCell;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::rc::{Rc, Weak};
+
+/// An error in a PEG grammar text, or a grammar that cannot be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PegError {
+  offset: usize,
+  message: String,
+}
+
+impl PegError {
+  fn new(offset: usize, message: impl Into<String>) -> Self {
+    Self {
+      offset,
+      message: message.into(),
+    }
+  }
+
+  /// Returns the offset of the error in the grammar text, in characters.
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+
+  /// Returns the description of the error.
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl Display for PegError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} at {}", self.message, self.offset)
+  }
+}
+
+impl std::error::Error for PegError {}
+
+/// A node of the tree produced by a [PegGrammar] parser, one per rule invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTree {
+  /// The name of the rule.
+  pub rule: String,
+  /// The range of the input matched by the rule.
+  pub span: Range<usize>,
+  /// The text matched by the rule.
+  pub text: String,
+  /// The trees of the rules invoked by this rule, in input order.
+  pub children: Vec<ParseTree>,
+}
+
+#[derive(Debug, Clone)]
+enum PegExpr {
+  Literal(Vec<char>),
+  Class(CharSet),
+  Any,
+  Rule(usize),
+  Seq(Vec<PegExpr>),
+  Choice(Vec<PegExpr>),
+  ZeroOrMore(Box<PegExpr>),
+  OneOrMore(Box<PegExpr>),
+  Optional(Box<PegExpr>),
+  And(Box<PegExpr>),
+  Not(Box<PegExpr>),
+}
+
+impl PegExpr {
+  fn is_nullable(&self, nullable: &[bool]) -> bool {
+    match self {
+      PegExpr::Literal(literal) => literal.is_empty(),
+      PegExpr::Class(_) | PegExpr::Any => false,
+      PegExpr::Rule(index) => nullable[*index],
+      PegExpr::Seq(exprs) => exprs.iter().all(|e| e.is_nullable(nullable)),
+      PegExpr::Choice(exprs) => exprs.iter().any(|e| e.is_nullable(nullable)),
+      PegExpr::OneOrMore(e) => e.is_nullable(nullable),
+      PegExpr::ZeroOrMore(_) | PegExpr::Optional(_) | PegExpr::And(_) | PegExpr::Not(_) => true,
+    }
+  }
+
+  /// Collects the rules that may be invoked before any input is consumed.
+  fn leftmost_rules(&self, nullable: &[bool], rules: &mut Vec<usize>) {
+    match self {
+      PegExpr::Literal(_) | PegExpr::Class(_) | PegExpr::Any => {}
+      PegExpr::Rule(index) => rules.push(*index),
+      PegExpr::Seq(exprs) => {
+        for e in exprs {
+          e.leftmost_rules(nullable, rules);
+          if !e.is_nullable(nullable) {
+            break;
+          }
+        }
+      }
+      PegExpr::Choice(exprs) => exprs.iter().for_each(|e| e.leftmost_rules(nullable, rules)),
+      PegExpr::ZeroOrMore(e) | PegExpr::OneOrMore(e) | PegExpr::Optional(e) | PegExpr::And(e) | PegExpr::Not(e) => {
+        e.leftmost_rules(nullable, rules)
+      }
+    }
+  }
+
+  /// Returns true if a repetition in this expression may match the empty string, which would never terminate.
+  fn has_nullable_repetition(&self, nullable: &[bool]) -> bool {
+    match self {
+      PegExpr::Literal(_) | PegExpr::Class(_) | PegExpr::Any | PegExpr::Rule(_) => false,
+      PegExpr::Seq(exprs) | PegExpr::Choice(exprs) => exprs.iter().any(|e| e.has_nullable_repetition(nullable)),
+      PegExpr::ZeroOrMore(e) | PegExpr::OneOrMore(e) => e.is_nullable(nullable) || e.has_nullable_repetition(nullable),
+      PegExpr::Optional(e) | PegExpr::And(e) | PegExpr::Not(e) => e.has_nullable_repetition(nullable),
+    }
+  }
+}
+
+struct PegRule {
+  name: String,
+  offset: usize,
+  expr: PegExpr,
+}
+
+/// A PEG grammar loaded at runtime, from which parsers producing [ParseTree]s are compiled.
+///
+/// The grammar text is a list of definitions `Name <- Expr`. Expressions are built from ordered choice `e1 / e2`,
+/// sequence `e1 e2`, the suffixes `e*`, `e+` and `e?`, the predicates `&e` and `!e`, grouping `(e)`, literals
+/// `'...'` or `"..."`, character classes `[a-z]`, any character `.`, and references to other rules. `#` starts a
+/// comment. Choices backtrack fully and repetitions are greedy, as usual for PEG.
+///
+/// References to undefined rules, left recursion and repetitions of expressions that match the empty string are
+/// rejected when the grammar is loaded.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// let grammar = PegGrammar::new(
+///   r#"
+///   expr   <- term (('+' / '-') term)*
+///   term   <- number / '(' expr ')'
+///   number <- [0-9]+
+///   "#,
+/// )
+/// .unwrap();
+///
+/// let input = "1+(2-3)".chars().collect::<Vec<_>>();
+/// let parser = grammar.parser("expr").unwrap() - end();
+///
+/// let tree = parser.parse(&input).success().unwrap();
+///
+/// assert_eq!(tree.rule, "expr");
+/// assert_eq!(tree.children.len(), 2);
+/// assert_eq!(tree.children[1].text, "(2-3)");
+/// assert_eq!(tree.children[1].children[0].rule, "expr");
+/// ```
+pub struct PegGrammar {
+  rules: Vec<PegRule>,
+}
+
+impl PegGrammar {
+  /// Load a grammar from its text.
+  pub fn new(text: &str) -> Result<Self, PegError> {
+    let grammar = GrammarReader::new(text).read()?;
+    grammar.validate()?;
+    Ok(grammar)
+  }
+
+  /// Returns the names of the rules, in declaration order.
+  pub fn rule_names(&self) -> impl Iterator<Item = &str> {
+    self.rules.iter().map(|rule| rule.name.as_str())
+  }
+
+  fn validate(&self) -> Result<(), PegError> {
+    let mut nullable = vec![false; self.rules.len()];
+    let mut changed = true;
+    while changed {
+      changed = false;
+      for (index, rule) in self.rules.iter().enumerate() {
+        if !nullable[index] && rule.expr.is_nullable(&nullable) {
+          nullable[index] = true;
+          changed = true;
+        }
+      }
+    }
+    let leftmost = self
+      .rules
+      .iter()
+      .map(|rule| {
+        let mut rules = vec![];
+        rule.expr.leftmost_rules(&nullable, &mut rules);
+        rules
+      })
+      .collect::<Vec<_>>();
+    for (index, rule) in self.rules.iter().enumerate() {
+      if rule.expr.has_nullable_repetition(&nullable) {
+        return Err(PegError::new(
+          rule.offset,
+          format!("repetition of an empty match in rule {}", rule.name),
+        ));
+      }
+      let mut visited = vec![false; self.rules.len()];
+      let mut stack = leftmost[index].clone();
+      while let Some(next) = stack.pop() {
+        if next == index {
+          return Err(PegError::new(
+            rule.offset,
+            format!("left recursion in rule {}", rule.name),
+          ));
+        }
+        if !visited[next] {
+          visited[next] = true;
+          stack.extend(&leftmost[next]);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Compile the rule `name` and the rules it refers to into a parser.
+  ///
+  /// Returns `None` if the grammar has no such rule. The parser does not borrow the grammar.
+  pub fn parser<'a>(&self, name: &str) -> Option<Parser<'a, char, ParseTree, impl ParserFn<'a, char, ParseTree>>> {
+    let start = self.rules.iter().position(|rule| rule.name == name)?;
+    let table: Rc<Vec<OnceCell<TreeFn<'a>>>> = Rc::new(self.rules.iter().map(|_| OnceCell::new()).collect());
+    for (index, rule) in self.rules.iter().enumerate() {
+      let body = compile(&rule.expr, &Rc::downgrade(&table));
+      let _ = table[index].set(invoke(rule.name.clone(), body));
+    }
+    Some(Parser::new(move |parse_context| {
+      table[start].get().unwrap()(parse_context)
+    }))
+  }
+}
+
+type TreesFn<'a> = Rc<dyn Fn(ParseContext<'a, char>) -> ParseResult<'a, char, Vec<ParseTree>> + 'a>;
+type TreeFn<'a> = Rc<dyn Fn(ParseContext<'a, char>) -> ParseResult<'a, char, ParseTree> + 'a>;
+
+fn erase<'a, P>(parser: P) -> TreesFn<'a>
+where
+  P: ParserRunner<'a, char, Vec<ParseTree>> + 'a, {
+  Rc::new(move |parse_context| parser.run(parse_context))
+}
+
+fn lift<'a>(f: TreesFn<'a>) -> Parser<'a, char, Vec<ParseTree>, impl ParserFn<'a, char, Vec<ParseTree>>> {
+  Parser::new(move |parse_context| f(parse_context))
+}
+
+/// Wraps the body of the rule `name` so that an invocation yields one tree.
+fn invoke<'a>(name: String, body: TreesFn<'a>) -> TreeFn<'a> {
+  Rc::new(
+    move |parse_context: ParseContext<'a, char>| match body(parse_context.clone()) {
+      ParseResult::Success { value, length, .. } => {
+        let start = parse_context.next_offset();
+        let tree = ParseTree {
+          rule: name.clone(),
+          span: start..start + length,
+          text: parse_context.slice_with_len(length).iter().collect(),
+          children: value,
+        };
+        ParseResult::successful(parse_context, tree, length)
+      }
+      ParseResult::Failure {
+        parse_context,
+        error,
+        committed_status,
+      } => ParseResult::failed(parse_context, error, committed_status),
+    },
+  )
+}
+
+fn concat(values: Vec<Vec<ParseTree>>) -> Vec<ParseTree> {
+  values.into_iter().flatten().collect()
+}
+
+fn compile<'a>(expr: &PegExpr, table: &Weak<Vec<OnceCell<TreeFn<'a>>>>) -> TreesFn<'a> {
+  match expr {
+    PegExpr::Literal(literal) => {
+      let literal = literal.clone();
+      Rc::new(move |parse_context| {
+        let input = parse_context.input();
+        for (index, c) in literal.iter().enumerate() {
+          match input.get(index) {
+            Some(actual) if actual == c => {}
+            Some(actual) => {
+              let msg = format!(
+                "literal {:?} expect: {:?}, found: {}",
+                String::from_iter(&literal),
+                c,
+                actual
+              );
+              let offset = parse_context.next_offset();
+              let pe = ParseError::of_mismatch(parse_context.original_input(), offset, index + 1, msg);
+              return ParseResult::failed(parse_context, pe, (index != 0).into());
+            }
+            None => return ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete()),
+          }
+        }
+        ParseResult::successful(parse_context, vec![], literal.len())
+      })
+    }
+    PegExpr::Class(set) => {
+      let set = set.clone();
+      erase(elm_pred_ref(move |c| set.contains(c)).map(|_| vec![]))
+    }
+    PegExpr::Any => erase(elm_pred_ref(|_| true).map(|_| vec![])),
+    PegExpr::Rule(index) => {
+      let (index, table) = (*index, table.clone());
+      Rc::new(move |parse_context| {
+        let table = table.upgrade().expect("the parser of the grammar was dropped");
+        let rule = table[index].get().unwrap().clone();
+        match rule(parse_context) {
+          ParseResult::Success {
+            parse_context,
+            value,
+            length,
+          } => ParseResult::successful(parse_context, vec![value], length),
+          ParseResult::Failure {
+            parse_context,
+            error,
+            committed_status,
+          } => ParseResult::failed(parse_context, error, committed_status),
+        }
+      })
+    }
+    PegExpr::Seq(exprs) => exprs
+      .iter()
+      .map(|e| compile(e, table))
+      .reduce(|left, right| {
+        erase((lift(left) + lift(right)).map(|(mut left, right)| {
+          left.extend(right);
+          left
+        }))
+      })
+      .unwrap_or_else(|| erase(unit().map(|_| vec![]))),
+    PegExpr::Choice(exprs) => exprs
+      .iter()
+      .map(|e| compile(e, table))
+      .reduce(|left, right| erase(lift(left).attempt().or(lift(right))))
+      .unwrap(),
+    PegExpr::ZeroOrMore(e) => erase(lift(compile(e, table)).attempt().of_many0().map(concat)),
+    PegExpr::OneOrMore(e) => erase(lift(compile(e, table)).attempt().of_many1().map(concat)),
+    PegExpr::Optional(e) => erase(lift(compile(e, table)).attempt().opt().map(Option::unwrap_or_default)),
+    PegExpr::And(e) => erase(lift(compile(e, table)).peek().map(|_| vec![])),
+    PegExpr::Not(e) => erase(lift(compile(e, table)).not().map(|_| vec![])),
+  }
+}
+
+/// A recursive descent reader of grammar texts.
+struct GrammarReader {
+  chars: Vec<char>,
+  index: usize,
+  names: Vec<String>,
+  references: Vec<(String, usize)>,
+}
+
+impl GrammarReader {
+  fn new(text: &str) -> Self {
+    Self {
+      chars: text.chars().collect(),
+      index: 0,
+      names: vec![],
+      references: vec![],
+    }
+  }
+
+  fn read(mut self) -> Result<PegGrammar, PegError> {
+    let mut definitions = vec![];
+    self.skip_spacing();
+    while self.index < self.chars.len() {
+      let offset = self.index;
+      let name = self
+        .identifier()
+        .ok_or_else(|| PegError::new(offset, "expected a rule name"))?;
+      if self.names.contains(&name) {
+        return Err(PegError::new(offset, format!("duplicate rule {}", name)));
+      }
+      if !self.arrow() {
+        return Err(PegError::new(self.index, "expected <-"));
+      }
+      self.names.push(name.clone());
+      definitions.push((name, offset, self.expression()?));
+    }
+    if definitions.is_empty() {
+      return Err(PegError::new(0, "no rules"));
+    }
+    let rules = definitions
+      .into_iter()
+      .map(|(name, offset, expr)| {
+        Ok(PegRule {
+          name,
+          offset,
+          expr: self.resolve(expr)?,
+        })
+      })
+      .collect::<Result<Vec<_>, PegError>>()?;
+    Ok(PegGrammar { rules })
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.index).copied()
+  }
+
+  fn skip_spacing(&mut self) {
+    while let Some(c) = self.peek() {
+      if c.is_whitespace() {
+        self.index += 1;
+      } else if c == '#' {
+        while self.peek().is_some_and(|c| c != '\n') {
+          self.index += 1;
+        }
+      } else {
+        break;
+      }
+    }
+  }
+
+  fn symbol(&mut self, symbol: &str) -> bool {
+    let length = symbol.chars().count();
+    if self.chars[self.index..].iter().take(length).copied().eq(symbol.chars()) {
+      self.index += length;
+      self.skip_spacing();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn arrow(&mut self) -> bool {
+    self.symbol("<-")
+  }
+
+  fn identifier(&mut self) -> Option<String> {
+    let start = self.index;
+    if !self.peek().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') {
+      return None;
+    }
+    while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+      self.index += 1;
+    }
+    let name = String::from_iter(&self.chars[start..self.index]);
+    self.skip_spacing();
+    Some(name)
+  }
+
+  fn expression(&mut self) -> Result<PegExpr, PegError> {
+    let mut alternatives = vec![self.sequence()?];
+    while self.symbol("/") {
+      alternatives.push(self.sequence()?);
+    }
+    Ok(match alternatives.len() {
+      1 => alternatives.pop().unwrap(),
+      _ => PegExpr::Choice(alternatives),
+    })
+  }
+
+  fn sequence(&mut self) -> Result<PegExpr, PegError> {
+    let mut exprs = vec![];
+    while let Some(expr) = self.prefix()? {
+      exprs.push(expr);
+    }
+    Ok(match exprs.len() {
+      1 => exprs.pop().unwrap(),
+      _ => PegExpr::Seq(exprs),
+    })
+  }
+
+  fn prefix(&mut self) -> Result<Option<PegExpr>, PegError> {
+    let offset = self.index;
+    let wrap: Option<fn(Box<PegExpr>) -> PegExpr> = if self.symbol("&") {
+      Some(PegExpr::And)
+    } else if self.symbol("!") {
+      Some(PegExpr::Not)
+    } else {
+      None
+    };
+    match (self.suffix()?, wrap) {
+      (Some(expr), Some(wrap)) => Ok(Some(wrap(Box::new(expr)))),
+      (expr, None) => Ok(expr),
+      (None, Some(_)) => Err(PegError::new(offset, "expected an expression after the predicate")),
+    }
+  }
+
+  fn suffix(&mut self) -> Result<Option<PegExpr>, PegError> {
+    let mut expr = match self.primary()? {
+      Some(expr) => expr,
+      None => return Ok(None),
+    };
+    loop {
+      expr = if self.symbol("*") {
+        PegExpr::ZeroOrMore(Box::new(expr))
+      } else if self.symbol("+") {
+        PegExpr::OneOrMore(Box::new(expr))
+      } else if self.symbol("?") {
+        PegExpr::Optional(Box::new(expr))
+      } else {
+        return Ok(Some(expr));
+      };
+    }
+  }
+
+  fn primary(&mut self) -> Result<Option<PegExpr>, PegError> {
+    let offset = self.index;
+    match self.peek() {
+      Some('(') => {
+        self.symbol("(");
+        let expr = self.expression()?;
+        if !self.symbol(")") {
+          return Err(PegError::new(self.index, "expected )"));
+        }
+        Ok(Some(expr))
+      }
+      Some('.') => {
+        self.symbol(".");
+        Ok(Some(PegExpr::Any))
+      }
+      Some(quote @ ('\'' | '"')) => self.literal(quote).map(Some),
+      Some('[') => self.class().map(Some),
+      _ => match self.identifier() {
+        Some(_) if self.peek() == Some('<') && self.chars.get(self.index + 1) == Some(&'-') => {
+          self.index = offset;
+          Ok(None)
+        }
+        Some(name) => {
+          self.references.push((name, offset));
+          Ok(Some(PegExpr::Rule(self.references.len() - 1)))
+        }
+        None => Ok(None),
+      },
+    }
+  }
+
+  fn literal(&mut self, quote: char) -> Result<PegExpr, PegError> {
+    let start = self.index;
+    let end = (start + 1..self.chars.len())
+      .try_fold(false, |escaped, index| match self.chars[index] {
+        c if c == quote && !escaped => Err(index),
+        '\\' => Ok(!escaped),
+        _ => Ok(false),
+      })
+      .err()
+      .ok_or_else(|| PegError::new(start, "unterminated literal"))?;
+    let mut literal = vec![];
+    let mut index = start + 1;
+    while index < end {
+      let (c, next) =
+        parse_class_char(&self.chars, index, end).map_err(|e| PegError::new(e.offset(), e.to_string()))?;
+      literal.push(c);
+      index = next;
+    }
+    self.index = end + 1;
+    self.skip_spacing();
+    Ok(PegExpr::Literal(literal))
+  }
+
+  fn class(&mut self) -> Result<PegExpr, PegError> {
+    let start = self.index;
+    let end = (start + 1..self.chars.len())
+      .try_fold(false, |escaped, index| match self.chars[index] {
+        ']' if !escaped => Err(index),
+        '\\' => Ok(!escaped),
+        _ => Ok(false),
+      })
+      .err()
+      .ok_or_else(|| PegError::new(start, "unterminated class"))?;
+    let class = String::from_iter(&self.chars[start..=end]);
+    let set = CharSet::from_class(&class).map_err(|e| PegError::new(start + e.offset(), e.to_string()))?;
+    self.index = end + 1;
+    self.skip_spacing();
+    Ok(PegExpr::Class(set))
+  }
+
+  /// Replaces the indices of references by the indices of the rules they name.
+  fn resolve(&self, expr: PegExpr) -> Result<PegExpr, PegError> {
+    let resolve_all = |exprs: Vec<PegExpr>| {
+      exprs
+        .into_iter()
+        .map(|e| self.resolve(e))
+        .collect::<Result<Vec<_>, _>>()
+    };
+    Ok(match expr {
+      PegExpr::Rule(reference) => {
+        let (name, offset) = &self.references[reference];
+        match self.names.iter().position(|n| n == name) {
+          Some(index) => PegExpr::Rule(index),
+          None => return Err(PegError::new(*offset, format!("undefined rule {}", name))),
+        }
+      }
+      PegExpr::Seq(exprs) => PegExpr::Seq(resolve_all(exprs)?),
+      PegExpr::Choice(exprs) => PegExpr::Choice(resolve_all(exprs)?),
+      PegExpr::ZeroOrMore(e) => PegExpr::ZeroOrMore(Box::new(self.resolve(*e)?)),
+      PegExpr::OneOrMore(e) => PegExpr::OneOrMore(Box::new(self.resolve(*e)?)),
+      PegExpr::Optional(e) => PegExpr::Optional(Box::new(self.resolve(*e)?)),
+      PegExpr::And(e) => PegExpr::And(Box::new(self.resolve(*e)?)),
+      PegExpr::Not(e) => PegExpr::Not(Box::new(self.resolve(*e)?)),
+      expr => expr,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+
+  #[test]
+  fn test_peg_predicates_and_choice() {
+    let grammar = PegGrammar::new(
+      r#"
+      # keywords are not identifiers
+      start   <- (keyword / ident) (' ' (keyword / ident))*
+      keyword <- ("if" / "else") !letter
+      ident   <- !keyword letter+ &(' ' / !.)
+      letter  <- [a-z]
+      "#,
+    )
+    .unwrap();
+    let input = "if iffy else".chars().collect::<Vec<_>>();
+
+    let tree = (grammar.parser("start").unwrap() - end())
+      .parse(&input)
+      .success()
+      .unwrap();
+
+    let rules = tree.children.iter().map(|t| t.rule.as_str()).collect::<Vec<_>>();
+    assert_eq!(rules, vec!["keyword", "ident", "keyword"]);
+    assert_eq!(tree.children[1].span, 3..7);
+    assert_eq!(tree.children[1].children.len(), 4);
+    assert!(grammar.parser("missing").is_none());
+  }
+
+  #[test]
+  fn test_peg_literal_mismatch_input() {
+    let grammar = PegGrammar::new(r#"start <- "ab" "cd""#).unwrap();
+    let input = "abce".chars().collect::<Vec<_>>();
+
+    let result = grammar.parser("start").unwrap().parse(&input);
+
+    let error = result.failure().unwrap();
+    assert_eq!(error.input_string().unwrap(), "ce");
+  }
+
+  #[test]
+  fn test_peg_grammar_errors() {
+    let cases = [
+      ("a <- 'x' b", 9, "undefined rule b"),
+      ("a <- b 'x'\nb <- 'y'? a", 0, "left recursion in rule a"),
+      ("a <- ('x'?)*", 0, "repetition of an empty match in rule a"),
+      ("a <- 'x", 5, "unterminated literal"),
+      ("a <- ('x'", 9, "expected )"),
+    ];
+    for (text, offset, message) in cases {
+      let error = PegGrammar::new(text).err().unwrap();
+      assert_eq!((error.offset(), error.message()), (offset, message), "{}", text);
+    }
+  }
+}
@@ -0,0 +1,727 @@
+use crate::parse_context::ParseContext;
+use crate::parse_error::ParseError;
+use crate::parse_result::ParseResult;
+use crate::parser::ParserRunner;
+use crate::util::{Bound, RangeArgument, Set};
+use std::fmt;
+use std::fmt::{Debug, Display};
+use std::ops::{Add, BitOr, Mul, Sub};
+use std::rc::Rc;
+
+type ParseFn<I, A> = dyn for<'a> Fn(ParseContext<'a, I>) -> ParseResult<'a, I, A>;
+type PrintFn<I, A> = dyn Fn(&A, &mut Vec<I>) -> Result<(), PrintError>;
+
+/// The error returned when a [Syntax] cannot print a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintError {
+  message: String,
+}
+
+impl PrintError {
+  pub fn new(message: String) -> Self {
+    Self { message }
+  }
+
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl Display for PrintError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "PrintError: {}", self.message)
+  }
+}
+
+impl std::error::Error for PrintError {}
+
+/// A parser that can also print its values back to input, so that one grammar definition yields both `parse` and
+/// `print`.
+///
+/// A `Syntax` is built from invertible primitives ([Syntax::tag], [Syntax::elm_of], [Syntax::repeat_sep],
+/// [Syntax::surround], [Syntax::map] with an inverse, ...) and the operators `+`, `*`, `-` and `|`, which behave like
+/// those of [Parser](crate::prelude::Parser). Unlike `Parser`, a `Syntax` is not tied to the lifetime of an input, so
+/// the same value parses any input, including the output of its own printer; see [check_round_trip] and
+/// [check_text_round_trip].
+///
+/// Printing is canonical: trivia such as whitespace is printed as declared in [Syntax::trivia], and alternatives are
+/// printed with the first one that accepts the value.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// # use twill_core::util::CharSet;
+///
+/// let ws = Syntax::trivia(CharSet::from_chars(" "), " ".chars());
+/// let key = Syntax::elm_of(CharSet::from_class("a-z_").unwrap())
+///   .repeat_sep(1.., None)
+///   .map(|cs| cs.into_iter().collect::<String>(), |s: &String| Some(s.chars().collect()));
+/// let value = Syntax::elm_of(CharSet::from_class("0-9").unwrap())
+///   .repeat_sep(1.., None)
+///   .map(|cs| String::from_iter(cs).parse::<u32>().unwrap(), |n: &u32| Some(n.to_string().chars().collect()));
+/// let entry = key - ws.clone() - Syntax::tag("=") - ws + value;
+/// let config = entry.repeat_sep(0.., Some(Syntax::tag("\n")));
+///
+/// let input = "width  =  80\nheight=24".chars().collect::<Vec<_>>();
+///
+/// let mut entries = config.parse(&input).success().unwrap();
+/// entries[0].1 = 120;
+///
+/// assert_eq!(config.print_string(&entries).unwrap(), "width = 120\nheight = 24");
+/// ```
+pub struct Syntax<I, A> {
+  parser: Rc<ParseFn<I, A>>,
+  printer: Rc<PrintFn<I, A>>,
+}
+
+impl<I, A> Clone for Syntax<I, A> {
+  fn clone(&self) -> Self {
+    Self {
+      parser: Rc::clone(&self.parser),
+      printer: Rc::clone(&self.printer),
+    }
+  }
+}
+
+impl<'a, I: 'a, A: 'a> ParserRunner<'a, I, A> for Syntax<I, A> {
+  fn run(&self, parse_context: ParseContext<'a, I>) -> ParseResult<'a, I, A> {
+    (self.parser)(parse_context)
+  }
+}
+
+/// Whether `count` items can be followed by another one.
+fn below_max<R: RangeArgument<usize>>(range: &R, count: usize) -> bool {
+  match range.end() {
+    Bound::Included(&max) => count < max,
+    Bound::Excluded(&max) => count + 1 < max,
+    Bound::Unbounded => true,
+  }
+}
+
+/// Whether `count` items satisfy the range.
+fn in_range<R: RangeArgument<usize>>(range: &R, count: usize) -> bool {
+  let above_min = match range.start() {
+    Bound::Included(&min) => count >= min,
+    Bound::Excluded(&min) => count > min,
+    Bound::Unbounded => true,
+  };
+  above_min && (count == 0 || below_max(range, count - 1))
+}
+
+impl<I, A> Syntax<I, A> {
+  /// Create a syntax from a parse function and a print function.
+  ///
+  /// The parse function must work for inputs of any lifetime, e.g. `|pc| some_parser().run(pc)` where
+  /// `some_parser` builds a [Parser](crate::prelude::Parser).
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use twill_core::prelude::*;
+  ///
+  /// let digits = Syntax::new(
+  ///   |pc| elm_digit_ref().of_many1().map(String::from_iter).run(pc),
+  ///   |s: &String, output: &mut Vec<char>| {
+  ///     output.extend(s.chars());
+  ///     Ok(())
+  ///   },
+  /// );
+  ///
+  /// assert_eq!(check_round_trip(&digits, 0, 10, |rng| (rng.below(1000) + 1).to_string()), Ok(()));
+  /// ```
+  pub fn new<P, W>(parser: P, printer: W) -> Self
+  where
+    P: for<'a> Fn(ParseContext<'a, I>) -> ParseResult<'a, I, A> + 'static,
+    W: Fn(&A, &mut Vec<I>) -> Result<(), PrintError> + 'static, {
+    Self {
+      parser: Rc::new(parser),
+      printer: Rc::new(printer),
+    }
+  }
+
+  /// Print `value` to the end of `output`.
+  pub fn print_to(&self, value: &A, output: &mut Vec<I>) -> Result<(), PrintError> {
+    (self.printer)(value, output)
+  }
+
+  /// Print `value`.
+  pub fn print(&self, value: &A) -> Result<Vec<I>, PrintError> {
+    let mut output = vec![];
+    self.print_to(value, &mut output)?;
+    Ok(output)
+  }
+
+  /// Returns a syntax that maps the parsed value with `f`, and prints through the inverse `g`.
+  ///
+  /// `g` returns `None` for values that `f` never produces, which makes printing fail.
+  ///
+  /// - f: a function from the parsed value
+  /// - g: the inverse of `f`
+  pub fn map<B, F, G>(self, f: F, g: G) -> Syntax<I, B>
+  where
+    I: 'static,
+    A: 'static,
+    F: Fn(A) -> B + 'static,
+    G: Fn(&B) -> Option<A> + 'static, {
+    let (parser, printer) = (self.parser, self.printer);
+    Syntax::new(
+      move |parse_context| parser(parse_context).map(&f),
+      move |value, output| match g(value) {
+        Some(value) => printer(&value, output),
+        None => Err(PrintError::new("map: the value has no inverse".to_string())),
+      },
+    )
+  }
+
+  /// Returns a syntax of `self` followed by `other`.
+  pub fn and_then<B>(self, other: Syntax<I, B>) -> Syntax<I, (A, B)>
+  where
+    I: 'static,
+    A: 'static,
+    B: 'static, {
+    let (p1, w1, p2, w2) = (self.parser, self.printer, other.parser, other.printer);
+    Syntax::new(
+      move |parse_context| match p1(parse_context.with_same_state()) {
+        ParseResult::Success {
+          value: a, length: n1, ..
+        } => match p2(parse_context.add_offset(n1)) {
+          ParseResult::Success {
+            value: b, length: n2, ..
+          } => ParseResult::successful(parse_context, (a, b), n1 + n2),
+          ParseResult::Failure {
+            parse_context,
+            error,
+            committed_status,
+          } => ParseResult::failed(parse_context, error, committed_status),
+        },
+        ParseResult::Failure {
+          parse_context,
+          error,
+          committed_status,
+        } => ParseResult::failed(parse_context, error, committed_status),
+      },
+      move |(a, b), output| {
+        w1(a, output)?;
+        w2(b, output)
+      },
+    )
+  }
+
+  /// Returns a syntax that tries `other` if `self` fails without consuming input.
+  ///
+  /// Prints with `self`, or with `other` if `self` cannot print the value.
+  pub fn or(self, other: Syntax<I, A>) -> Syntax<I, A>
+  where
+    I: 'static,
+    A: 'static, {
+    let (p1, w1, p2, w2) = (self.parser, self.printer, other.parser, other.printer);
+    Syntax::new(
+      move |parse_context| {
+        let result = p1(parse_context.with_same_state());
+        match result.committed_status() {
+          Some(committed_status) if committed_status.is_uncommitted() => p2(parse_context),
+          _ => result,
+        }
+      },
+      move |value, output| {
+        let length = output.len();
+        w1(value, output).or_else(|_| {
+          output.truncate(length);
+          w2(value, output)
+        })
+      },
+    )
+  }
+
+  /// Returns a syntax whose parse failures are uncommitted, so that [Syntax::or] tries the next alternative even
+  /// after `self` consumed input.
+  pub fn attempt(self) -> Syntax<I, A>
+  where
+    I: 'static,
+    A: 'static, {
+    let parser = self.parser;
+    Syntax {
+      parser: Rc::new(move |parse_context| parser(parse_context).with_uncommitted()),
+      printer: self.printer,
+    }
+  }
+
+  /// Returns a syntax that repeats `self` a number of times in `range`, separated by `separator_opt`.
+  ///
+  /// - range: a range of the number of repetitions
+  /// - separator_opt: an optional separator
+  pub fn repeat_sep<R>(self, range: R, separator_opt: Option<Syntax<I, ()>>) -> Syntax<I, Vec<A>>
+  where
+    I: 'static,
+    A: 'static,
+    R: RangeArgument<usize> + 'static, {
+    let range = Rc::new(range);
+    let print_range = Rc::clone(&range);
+    let print_separator = separator_opt.clone();
+    let (parser, printer) = (self.parser, self.printer);
+    Syntax::new(
+      move |parse_context| {
+        let mut items = vec![];
+        let mut all_length = 0;
+        while below_max(&*range, items.len()) {
+          let mut sep_length = 0;
+          if let (Some(separator), false) = (&separator_opt, items.is_empty()) {
+            match separator.run(parse_context.add_offset(all_length)) {
+              ParseResult::Success { length, .. } => sep_length = length,
+              ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => break,
+              ParseResult::Failure {
+                parse_context,
+                error,
+                committed_status,
+              } => return ParseResult::failed(parse_context, error, committed_status),
+            }
+          }
+          match parser(parse_context.add_offset(all_length + sep_length)) {
+            // A repetition that consumes nothing never ends, so stop once the minimum is reached.
+            ParseResult::Success { length, .. } if sep_length + length == 0 && in_range(&*range, items.len()) => break,
+            ParseResult::Success { value, length, .. } => {
+              items.push(value);
+              all_length += sep_length + length;
+            }
+            ParseResult::Failure { committed_status, .. } if committed_status.is_uncommitted() => break,
+            ParseResult::Failure {
+              parse_context,
+              error,
+              committed_status,
+            } => return ParseResult::failed(parse_context, error, committed_status),
+          }
+        }
+        if !in_range(&*range, items.len()) {
+          let msg = format!("repeat_sep: too few items: {}", items.len());
+          let offset = parse_context.next_offset();
+          let pe = ParseError::of_mismatch(parse_context.original_input(), offset, all_length, msg);
+          return ParseResult::failed(parse_context, pe, (all_length > 0).into());
+        }
+        ParseResult::successful(parse_context, items, all_length)
+      },
+      move |items: &Vec<A>, output| {
+        if !in_range(&*print_range, items.len()) {
+          return Err(PrintError::new(format!(
+            "repeat_sep: item count out of range: {}",
+            items.len()
+          )));
+        }
+        for (index, item) in items.iter().enumerate() {
+          if let (Some(separator), true) = (&print_separator, index > 0) {
+            separator.print_to(&(), output)?;
+          }
+          printer(item, output)?;
+        }
+        Ok(())
+      },
+    )
+  }
+
+  /// Returns a syntax of `self` between `left` and `right`.
+  pub fn surround(left: Syntax<I, ()>, syntax: Syntax<I, A>, right: Syntax<I, ()>) -> Syntax<I, A>
+  where
+    I: 'static,
+    A: Clone + 'static, {
+    left * syntax - right
+  }
+}
+
+impl<I> Syntax<I, ()> {
+  /// Returns a syntax of the element `element`.
+  pub fn elm(element: I) -> Self
+  where
+    I: PartialEq + Clone + Debug + 'static, {
+    let expected = element.clone();
+    Syntax::new(
+      move |parse_context: ParseContext<I>| match parse_context.input().first() {
+        Some(actual) if *actual == expected => ParseResult::successful(parse_context, (), 1),
+        Some(actual) => {
+          let msg = format!("expect: {:?}, found: {:?}", expected, actual);
+          let offset = parse_context.next_offset();
+          let pe = ParseError::of_mismatch(parse_context.original_input(), offset, 1, msg);
+          ParseResult::failed_with_uncommitted(parse_context, pe)
+        }
+        None => ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete()),
+      },
+      move |_, output| {
+        output.push(element.clone());
+        Ok(())
+      },
+    )
+  }
+
+  /// Returns a syntax that skips zero or more elements of `set`, and prints `printed`.
+  ///
+  /// - set: the elements to skip
+  /// - printed: the canonical trivia to print
+  pub fn trivia<S>(set: S, printed: impl IntoIterator<Item = I>) -> Self
+  where
+    I: Clone + Debug + 'static,
+    S: Set<I> + 'static, {
+    let printed = printed.into_iter().collect::<Vec<_>>();
+    Syntax::new(
+      move |parse_context: ParseContext<I>| {
+        let length = parse_context.input().iter().take_while(|e| set.contains(e)).count();
+        ParseResult::successful(parse_context, (), length)
+      },
+      move |_, output| {
+        output.extend(printed.iter().cloned());
+        Ok(())
+      },
+    )
+  }
+}
+
+impl<I> Syntax<I, I> {
+  /// Returns a syntax of an element of `set`.
+  pub fn elm_of<S>(set: S) -> Self
+  where
+    I: Clone + Debug + 'static,
+    S: Set<I> + 'static, {
+    let set = Rc::new(set);
+    let print_set = Rc::clone(&set);
+    Syntax::new(
+      move |parse_context: ParseContext<I>| match parse_context.input().first() {
+        Some(actual) if set.contains(actual) => ParseResult::successful(parse_context, actual.clone(), 1),
+        Some(actual) => {
+          let msg = format!("expect an element of {}, found: {:?}", set.to_str(), actual);
+          let offset = parse_context.next_offset();
+          let pe = ParseError::of_mismatch(parse_context.original_input(), offset, 1, msg);
+          ParseResult::failed_with_uncommitted(parse_context, pe)
+        }
+        None => ParseResult::failed_with_uncommitted(parse_context, ParseError::of_in_complete()),
+      },
+      move |value, output| {
+        if !print_set.contains(value) {
+          let msg = format!("{:?} is not an element of {}", value, print_set.to_str());
+          return Err(PrintError::new(msg));
+        }
+        output.push(value.clone());
+        Ok(())
+      },
+    )
+  }
+}
+
+impl Syntax<char, ()> {
+  /// Returns a syntax of the string `tag`.
+  pub fn tag(tag: &str) -> Self {
+    let tag = tag.chars().collect::<Vec<_>>();
+    let print_tag = tag.clone();
+    Syntax::new(
+      move |parse_context: ParseContext<char>| {
+        let input = parse_context.input();
+        match input.iter().zip(&tag).position(|(actual, expected)| actual != expected) {
+          Some(index) => {
+            let msg = format!(
+              "tag {:?} expect: {:?}, found: {:?}",
+              String::from_iter(&tag),
+              tag[index],
+              input[index]
+            );
+            let offset = parse_context.next_offset();
+            let pe = ParseError::of_mismatch(parse_context.original_input(), offset, index + 1, msg);
+            ParseResult::failed(parse_context, pe, (index > 0).into())
+          }
+          None if input.len() < tag.len() => ParseResult::failed_with_uncommitted(
+            parse_context,
+            ParseError::of_in_complete_needed(tag.len() - input.len()),
+          ),
+          None => ParseResult::successful(parse_context, (), tag.len()),
+        }
+      },
+      move |_, output| {
+        output.extend(&print_tag);
+        Ok(())
+      },
+    )
+  }
+}
+
+impl<A> Syntax<char, A> {
+  /// Print `value` as a string.
+  pub fn print_string(&self, value: &A) -> Result<String, PrintError> {
+    self.print(value).map(String::from_iter)
+  }
+}
+
+impl<I: 'static, A: 'static, B: 'static> Add<Syntax<I, B>> for Syntax<I, A> {
+  type Output = Syntax<I, (A, B)>;
+
+  fn add(self, rhs: Syntax<I, B>) -> Self::Output {
+    self.and_then(rhs)
+  }
+}
+
+impl<I: 'static, B: Clone + 'static> Mul<Syntax<I, B>> for Syntax<I, ()> {
+  type Output = Syntax<I, B>;
+
+  fn mul(self, rhs: Syntax<I, B>) -> Self::Output {
+    self.and_then(rhs).map(|(_, b)| b, |b: &B| Some(((), b.clone())))
+  }
+}
+
+impl<I: 'static, A: Clone + 'static> Sub<Syntax<I, ()>> for Syntax<I, A> {
+  type Output = Syntax<I, A>;
+
+  fn sub(self, rhs: Syntax<I, ()>) -> Self::Output {
+    self.and_then(rhs).map(|(a, _)| a, |a: &A| Some((a.clone(), ())))
+  }
+}
+
+impl<I: 'static, A: 'static> BitOr for Syntax<I, A> {
+  type Output = Syntax<I, A>;
+
+  fn bitor(self, rhs: Syntax<I, A>) -> Self::Output {
+    self.or(rhs)
+  }
+}
+
+/// A small deterministic random number generator for the values of [check_round_trip].
+#[derive(Debug, Clone)]
+pub struct RoundTripRng {
+  state: u64,
+}
+
+impl RoundTripRng {
+  /// Create a generator from `seed`. The same seed produces the same values.
+  pub fn new(seed: u64) -> Self {
+    Self { state: seed }
+  }
+
+  /// Returns the next random `u64` (SplitMix64).
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+  }
+
+  /// Returns a random number in `0..bound`. `bound` must not be 0.
+  pub fn below(&mut self, bound: usize) -> usize {
+    (self.next_u64() % bound as u64) as usize
+  }
+
+  /// Returns a random `bool`.
+  pub fn next_bool(&mut self) -> bool {
+    self.next_u64() & 1 == 1
+  }
+}
+
+/// Checks that `syntax` prints `value` to output that parses back to the same value, consuming all of it.
+fn check_value_round_trip<I, A>(syntax: &Syntax<I, A>, value: A) -> Result<(), String>
+where
+  I: Debug,
+  A: PartialEq + Debug, {
+  let printed = syntax
+    .print(&value)
+    .map_err(|e| format!("cannot print {:?}: {}", value, e))?;
+  match syntax.parse(&printed) {
+    ParseResult::Success {
+      value: parsed, length, ..
+    } if parsed == value && length == printed.len() => Ok(()),
+    ParseResult::Success {
+      value: parsed, length, ..
+    } => Err(format!(
+      "{:?} printed as {:?} parsed back as {:?} consuming {} of {} elements",
+      value,
+      printed,
+      parsed,
+      length,
+      printed.len()
+    )),
+    ParseResult::Failure { error, .. } => Err(format!(
+      "{:?} printed as {:?} failed to parse: {}",
+      value, printed, error
+    )),
+  }
+}
+
+/// Checks that `syntax` prints `cases` values made by `generate` to output that parses back to the same value,
+/// consuming all of it.
+///
+/// The values are drawn from a [RoundTripRng] seeded with `seed`, so a failure is reproduced by the same seed. Returns
+/// a message describing the first value that does not round-trip.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+///
+/// let boolean = Syntax::tag("true").map(|_| true, |b: &bool| b.then_some(()))
+///   | Syntax::tag("false").map(|_| false, |b: &bool| (!b).then_some(()));
+/// let list = Syntax::surround(Syntax::tag("["), boolean.repeat_sep(0.., Some(Syntax::tag(","))), Syntax::tag("]"));
+///
+/// let result = check_round_trip(&list, 42, 100, |rng| {
+///   let len = rng.below(5);
+///   (0..len).map(|_| rng.next_bool()).collect()
+/// });
+///
+/// assert!(result.is_ok());
+/// ```
+pub fn check_round_trip<I, A, G>(
+  syntax: &Syntax<I, A>,
+  seed: u64,
+  cases: usize,
+  mut generate: G,
+) -> Result<(), String>
+where
+  I: Debug,
+  A: PartialEq + Debug,
+  G: FnMut(&mut RoundTripRng) -> A, {
+  let mut rng = RoundTripRng::new(seed);
+  for case in 0..cases {
+    let value = generate(&mut rng);
+    check_value_round_trip(syntax, value).map_err(|e| format!("case {} of seed {}: {}", case, seed, e))?;
+  }
+  Ok(())
+}
+
+/// Checks that each of `inputs` parses to a value that prints to output parsing back to the same value.
+///
+/// Each input must be consumed entirely. The printed output may differ from the input, e.g. in trivia, as long as
+/// both parse to the same value. Returns a message describing the first input that does not round-trip.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// # use twill_core::util::CharSet;
+///
+/// let ws = Syntax::trivia(CharSet::from_chars(" "), " ".chars());
+/// let digit = Syntax::elm_of(CharSet::from_class("0-9").unwrap());
+/// let list = digit.repeat_sep(0.., Some(ws.clone() - Syntax::tag(",") - ws));
+///
+/// let inputs = ["1,2 ,  3", ""].map(|s| s.chars().collect::<Vec<_>>());
+///
+/// assert!(check_text_round_trip(&list, inputs.iter().map(Vec::as_slice)).is_ok());
+/// ```
+pub fn check_text_round_trip<'t, I, A>(
+  syntax: &Syntax<I, A>,
+  inputs: impl IntoIterator<Item = &'t [I]>,
+) -> Result<(), String>
+where
+  I: Debug + 't,
+  A: PartialEq + Debug, {
+  for input in inputs {
+    match syntax.parse(input) {
+      ParseResult::Success { value, length, .. } if length == input.len() => {
+        check_value_round_trip(syntax, value).map_err(|e| format!("{:?} parsed as {}", input, e))?
+      }
+      ParseResult::Success { length, .. } => {
+        return Err(format!(
+          "{:?} parsed consuming {} of {} elements",
+          input,
+          length,
+          input.len()
+        ))
+      }
+      ParseResult::Failure { error, .. } => return Err(format!("{:?} failed to parse: {}", input, error)),
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+  use crate::util::CharSet;
+
+  #[derive(Debug, Clone, PartialEq)]
+  enum Value {
+    Flag(bool),
+    Number(u32),
+  }
+
+  fn value() -> Syntax<char, Value> {
+    let digits = Syntax::elm_of(CharSet::from_class("0-9").unwrap()).repeat_sep(1..10, None);
+    let number = digits.map(
+      |cs| Value::Number(String::from_iter(cs).parse().unwrap()),
+      |v: &Value| match v {
+        Value::Number(n) => Some(n.to_string().chars().collect()),
+        _ => None,
+      },
+    );
+    let flag = |name: &str, b: bool| {
+      Syntax::tag(name).attempt().map(
+        move |_| Value::Flag(b),
+        move |v: &Value| (*v == Value::Flag(b)).then_some(()),
+      )
+    };
+    number | flag("on", true) | flag("off", false)
+  }
+
+  #[test]
+  fn test_syntax_round_trip() {
+    let mut values = vec![
+      vec![],
+      vec![Value::Number(0)],
+      vec![Value::Flag(false), Value::Number(42), Value::Flag(true)],
+    ];
+    let list = value().repeat_sep(0.., Some(Syntax::tag(", ")));
+
+    assert_eq!(check_round_trip(&list, 7, 3, |_| values.remove(0)), Ok(()));
+    assert!(list.print(&vec![Value::Number(1_000_000_000)]).is_err());
+
+    let generate = |rng: &mut RoundTripRng| {
+      let len = rng.below(4);
+      (0..len)
+        .map(|_| match rng.below(3) {
+          0 => Value::Flag(rng.next_bool()),
+          _ => Value::Number(rng.below(1000) as u32),
+        })
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(check_round_trip(&list, 1, 200, generate), Ok(()));
+
+    let unseparated = value().repeat_sep(0.., None);
+    let error = check_round_trip(&unseparated, 1, 200, generate).unwrap_err();
+    assert!(error.contains("of seed 1"));
+
+    let input = "on,  12, off".chars().collect::<Vec<_>>();
+    let spaced = value().repeat_sep(
+      0..,
+      Some(Syntax::tag(",") - Syntax::trivia(CharSet::from_chars(" "), " ".chars())),
+    );
+    assert_eq!(check_text_round_trip(&spaced, [input.as_slice()]), Ok(()));
+    assert!(check_text_round_trip(&spaced, [&input[..4]]).is_err());
+  }
+
+  #[test]
+  fn test_repeat_sep_of_empty_items_terminates() {
+    let input = "ab".chars().collect::<Vec<_>>();
+    let word = Syntax::elm_of(CharSet::from_class("a-z").unwrap()).repeat_sep(0.., None);
+
+    let result = word.clone().repeat_sep(0.., None).parse(&input);
+    assert_eq!(result.success().unwrap(), vec![vec!['a', 'b']]);
+
+    let result = word.repeat_sep(2.., None).parse(&input);
+    assert_eq!(result.success().unwrap(), vec![vec!['a', 'b'], vec![]]);
+  }
+
+  #[test]
+  fn test_tag_commits_on_partial_match() {
+    let input = "ox".chars().collect::<Vec<_>>();
+
+    let result = Syntax::tag("on").parse(&input);
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Committed));
+
+    let result = Syntax::tag("off").parse(&input[1..]);
+    assert_eq!(result.committed_status(), Some(CommittedStatus::Uncommitted));
+  }
+
+  #[test]
+  fn test_syntax_print_errors() {
+    let key = Syntax::elm_of(CharSet::from_class("a-z").unwrap());
+
+    assert!(key.print(&'A').is_err());
+    assert!(key.repeat_sep(2..=3, None).print(&vec!['a']).is_err());
+
+    let input = "a=on".chars().collect::<Vec<_>>();
+    let entry = Syntax::elm_of(CharSet::from_class("a-z").unwrap()) - Syntax::elm('=') + value();
+    assert_eq!(entry.parse(&input).success().unwrap(), ('a', Value::Flag(true)));
+    assert_eq!(entry.print_string(&('b', Value::Number(7))).unwrap(), "b=7");
+  }
+}
@@ -0,0 +1,185 @@
+use crate::parse_error::ParseError;
+use std::fmt;
+use std::fmt::{Debug, Display};
+use std::ops::Range;
+
+/// A token produced by a lexer, the input element of token-level parsers.
+///
+/// Implement this trait for the token type of a two-phase lexer+parser pipeline, then match tokens with [token] and
+/// [token_map]. Offsets of [ParseError] count tokens; [ParseError::source_span] maps them back to the source text, and
+/// [ParseError::display_source] prints an error with those spans.
+///
+/// [token]: crate::prelude::token
+/// [token_map]: crate::prelude::token_map
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// use std::ops::Range;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Kind {
+///   Num,
+///   Plus,
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Tok {
+///   Num(i64, Range<usize>),
+///   Plus(Range<usize>),
+/// }
+///
+/// impl TokenElement for Tok {
+///   type Kind = Kind;
+///
+///   fn kind(&self) -> Kind {
+///     match self {
+///       Tok::Num(..) => Kind::Num,
+///       Tok::Plus(..) => Kind::Plus,
+///     }
+///   }
+///
+///   fn span(&self) -> Range<usize> {
+///     match self {
+///       Tok::Num(_, span) | Tok::Plus(span) => span.clone(),
+///     }
+///   }
+/// }
+///
+/// // "1 + 2"
+/// let input = [Tok::Num(1, 0..1), Tok::Plus(2..3), Tok::Num(2, 4..5)];
+///
+/// let num = token_map(|t: &Tok| match t {
+///   Tok::Num(n, _) => Some(*n),
+///   _ => None,
+/// });
+/// let parser = num.clone() - token(Kind::Plus) + num;
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), (1, 2));
+///
+/// let error = token(Kind::Plus).parse(&input).failure().unwrap();
+///
+/// assert_eq!(error.source_span(), Some(0..1));
+/// ```
+pub trait TokenElement: Debug {
+  /// The discriminant of a token, usually a field-less enum.
+  type Kind: PartialEq + Debug;
+
+  /// Returns the discriminant of this token.
+  fn kind(&self) -> Self::Kind;
+
+  /// Returns the range of this token in the source text.
+  fn span(&self) -> Range<usize>;
+}
+
+impl<T: TokenElement> TokenElement for &T {
+  type Kind = T::Kind;
+
+  fn kind(&self) -> Self::Kind {
+    (**self).kind()
+  }
+
+  fn span(&self) -> Range<usize> {
+    (**self).span()
+  }
+}
+
+/// A token with its kind, text and span, produced by [TokenizerSpec::tokenize].
+///
+/// [TokenizerSpec::tokenize]: crate::prelude::TokenizerSpec::tokenize
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<K> {
+  /// The kind of the token.
+  pub kind: K,
+  /// The matched text.
+  pub text: String,
+  /// The range of the token in the source text.
+  pub span: Range<usize>,
+}
+
+impl<K: PartialEq + Debug + Clone> TokenElement for Token<K> {
+  type Kind = K;
+
+  fn kind(&self) -> K {
+    self.kind.clone()
+  }
+
+  fn span(&self) -> Range<usize> {
+    self.span.clone()
+  }
+}
+
+/// Returns the source range covered by `length` tokens at `offset` of `input`.
+///
+/// An empty range is placed at the start of the token at `offset`, or at the end of the last token past the input.
+pub(crate) fn source_span_of<T: TokenElement>(input: &[T], offset: usize, length: usize) -> Range<usize> {
+  match input.get(offset..offset + length) {
+    Some([first, .., last]) => first.span().start..last.span().end,
+    Some([token]) => token.span(),
+    _ => {
+      let position = match input.get(offset) {
+        Some(token) => token.span().start,
+        None => input.last().map_or(0, |token| token.span().end),
+      };
+      position..position
+    }
+  }
+}
+
+impl<T: TokenElement> ParseError<'_, T> {
+  /// Returns the range of the source text covered by the tokens of this error.
+  ///
+  /// Returns `None` for errors that carry no input, such as [ParseError::Incomplete].
+  pub fn source_span(&self) -> Option<Range<usize>> {
+    match self {
+      ParseError::Mismatch {
+        input, offset, length, ..
+      }
+      | ParseError::Conversion {
+        input, offset, length, ..
+      } => Some(source_span_of(input, *offset, *length)),
+      ParseError::Expect { inner, .. } => inner.source_span(),
+      ParseError::Custom { inner: Some(inner), .. } => inner.source_span(),
+      ParseError::Custom { inner: None, .. }
+      | ParseError::Incomplete
+      | ParseError::IncompleteNeeded { .. }
+      | ParseError::Permutation { .. } => None,
+    }
+  }
+
+  /// Returns a value that displays this error with source spans in place of token offsets.
+  pub fn display_source(&self) -> SourceDisplay<'_, '_, T> {
+    SourceDisplay(self)
+  }
+}
+
+/// Displays a [ParseError] of tokens with source spans. See [ParseError::display_source].
+pub struct SourceDisplay<'e, 'a, T: 'a>(&'e ParseError<'a, T>);
+
+impl<T: TokenElement> Display for SourceDisplay<'_, '_, T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let span = self.0.source_span();
+    match (self.0, span) {
+      (ParseError::Mismatch { message, .. }, Some(span)) => write!(f, "Mismatch at {:?}: {}", span, message),
+      (ParseError::Conversion { message, .. }, Some(span)) => {
+        write!(f, "Conversion failed at {:?}: {}", span, message)
+      }
+      (ParseError::Expect { message, inner, .. }, Some(span)) => {
+        write!(f, "{} at {:?}: {}", message, span, inner.display_source())
+      }
+      (
+        ParseError::Custom {
+          message,
+          inner: Some(inner),
+          ..
+        },
+        Some(span),
+      ) => write!(f, "{} at {:?}, (inner: {})", message, span, inner.display_source()),
+      (error, _) => write!(f, "{}", error),
+    }
+  }
+}
@@ -0,0 +1,359 @@
+use crate::combinators::CharRegex;
+use crate::parse_error::ParseError;
+use crate::token::Token;
+use crate::util::Set;
+use regex_automata::hybrid::dfa::{Cache, DFA};
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+use std::cell::{OnceCell, RefCell};
+
+enum Matcher {
+  Literal(Vec<char>),
+  Regex(Box<CharRegex>),
+  Set(Box<dyn Set<char>>),
+}
+
+impl Matcher {
+  /// Returns the number of elements matched at the start of `input`.
+  fn find_len(&self, input: &[char]) -> usize {
+    match self {
+      Matcher::Literal(literal) if input.starts_with(literal) => literal.len(),
+      Matcher::Literal(_) => 0,
+      Matcher::Regex(regex) => regex.find_len(input).unwrap_or(0),
+      Matcher::Set(set) => input.iter().take_while(|c| set.contains(c)).count(),
+    }
+  }
+}
+
+struct TokenRule<K> {
+  kind: Option<K>,
+  matcher: Matcher,
+  /// The regular expression of a literal or regex rule, compiled into [RuleAutomaton].
+  pattern: Option<String>,
+  priority: i32,
+}
+
+/// The literal and regex rules compiled into one lazy DFA, which finds every rule matching at a position in a single
+/// pass.
+///
+/// The DFA reports all matches of each rule, so the longest of them only bounds the match of a regex rule, whose
+/// alternations and lazy repetitions may prefer a shorter one.
+struct RuleAutomaton {
+  dfa: DFA,
+  cache: RefCell<Cache>,
+  /// The index of the rule of each pattern.
+  rules: Vec<usize>,
+}
+
+impl RuleAutomaton {
+  fn new<K>(rules: &[TokenRule<K>]) -> Option<Self> {
+    let (rules, patterns): (Vec<_>, Vec<_>) = rules
+      .iter()
+      .enumerate()
+      .filter_map(|(index, rule)| Some((index, rule.pattern.as_deref()?)))
+      .unzip();
+    let dfa = DFA::builder()
+      .configure(DFA::config().match_kind(MatchKind::All).unicode_word_boundary(true))
+      .build_many(&patterns)
+      .ok()?;
+    let cache = RefCell::new(dfa.create_cache());
+    Some(Self { dfa, cache, rules })
+  }
+
+  /// Raises the bound of each rule in `bounds` to the end of its longest match at the start of `input`.
+  ///
+  /// Returns `None` if the DFA gives up, e.g. on a Unicode word boundary after non-ASCII text.
+  fn bound_matches(&self, input: &[char], bounds: &mut [usize]) -> Option<()> {
+    let mut cache = self.cache.borrow_mut();
+    let config = start::Config::new().anchored(Anchored::Yes);
+    let mut state = self.dfa.start_state(&mut cache, &config).ok()?;
+    let mut buf = [0; 4];
+    for (index, c) in input.iter().enumerate() {
+      for (byte_index, byte) in c.encode_utf8(&mut buf).bytes().enumerate() {
+        state = self.dfa.next_state(&mut cache, state, byte).ok()?;
+        if state.is_tagged() {
+          if state.is_quit() {
+            return None;
+          }
+          // Matches are delayed by one byte, so a match state here means the matches end before this element.
+          if state.is_match() && byte_index == 0 {
+            for match_index in 0..self.dfa.match_len(&cache, state) {
+              bounds[self.rules[self.dfa.match_pattern(&cache, state, match_index)]] = index;
+            }
+          }
+          if state.is_dead() {
+            return Some(());
+          }
+        }
+      }
+    }
+    state = self.dfa.next_eoi_state(&mut cache, state).ok()?;
+    if state.is_match() {
+      for match_index in 0..self.dfa.match_len(&cache, state) {
+        bounds[self.rules[self.dfa.match_pattern(&cache, state, match_index)]] = input.len();
+      }
+    }
+    Some(())
+  }
+}
+
+/// A declarative description of tokens, from which [TokenizerSpec::tokenize] splits `char` input into [Token]s.
+///
+/// At each position the longest match wins. Matches of the same length are resolved by the
+/// higher priority, then by the rule declared first. Skip rules (whitespace, comments) match like other rules but
+/// produce no token. The resulting `&[Token<K>]` is an input for [token] and [token_map].
+///
+/// Literal and regex rules are compiled into one automaton on the first call to [TokenizerSpec::tokenize], which finds
+/// the rules matching at a position in a single pass. Set rules are checked one by one.
+///
+/// [token]: crate::prelude::token
+/// [token_map]: crate::prelude::token_map
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// # use twill_core::util::CharSet;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Kind {
+///   Let,
+///   Ident,
+///   Number,
+///   Eq,
+/// }
+///
+/// let spec = TokenizerSpec::new()
+///   .with_skip_set(CharSet::from_chars(" \t\n"))
+///   .with_skip_regex(r"//[^\n]*")
+///   .unwrap()
+///   .with_regex(Kind::Ident, r"[a-z_][a-z0-9_]*")
+///   .unwrap()
+///   .with_set(Kind::Number, CharSet::from_class("0-9").unwrap())
+///   .with_literal(Kind::Eq, "=")
+///   .with_priority(1)
+///   .with_literal(Kind::Let, "let");
+///
+/// let text = "let letter = 42 // answer";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let tokens = spec.tokenize(&input).unwrap();
+///
+/// let kinds = tokens.iter().map(|t| t.kind).collect::<Vec<_>>();
+/// assert_eq!(kinds, vec![Kind::Let, Kind::Ident, Kind::Eq, Kind::Number]);
+/// assert_eq!(tokens[1].text, "letter");
+/// assert_eq!(tokens[1].span, 4..10);
+///
+/// let number = token_map(|t: &Token<Kind>| t.text.parse::<u32>().ok());
+/// let parser = token(Kind::Let) * token(Kind::Ident) * token(Kind::Eq) * number;
+///
+/// assert_eq!(parser.parse(&tokens).success().unwrap(), 42);
+/// ```
+pub struct TokenizerSpec<K> {
+  rules: Vec<TokenRule<K>>,
+  priority: i32,
+  automaton: OnceCell<Option<RuleAutomaton>>,
+}
+
+impl<K> Default for TokenizerSpec<K> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<K> TokenizerSpec<K> {
+  /// Create a spec without rules.
+  pub fn new() -> Self {
+    Self {
+      rules: vec![],
+      priority: 0,
+      automaton: OnceCell::new(),
+    }
+  }
+
+  fn with_matcher(mut self, kind: Option<K>, matcher: Matcher, pattern: Option<String>) -> Self {
+    self.rules.push(TokenRule {
+      kind,
+      matcher,
+      pattern,
+      priority: self.priority,
+    });
+    self.automaton = OnceCell::new();
+    self
+  }
+
+  /// Set the priority of the rules added after this call. The initial priority is 0.
+  pub fn with_priority(mut self, priority: i32) -> Self {
+    self.priority = priority;
+    self
+  }
+
+  /// Add a token that matches `literal` exactly.
+  pub fn with_literal(self, kind: K, literal: &str) -> Self {
+    let pattern = regex_syntax::escape(literal);
+    self.with_matcher(Some(kind), Matcher::Literal(literal.chars().collect()), Some(pattern))
+  }
+
+  /// Add a token that matches the regular expression `pattern`.
+  pub fn with_regex(self, kind: K, pattern: &str) -> Result<Self, regex::Error> {
+    let matcher = Matcher::Regex(Box::new(CharRegex::new(pattern)?));
+    Ok(self.with_matcher(Some(kind), matcher, Some(pattern.to_string())))
+  }
+
+  /// Add a token that matches one or more elements of `set`.
+  pub fn with_set<S>(self, kind: K, set: S) -> Self
+  where
+    S: Set<char> + 'static, {
+    self.with_matcher(Some(kind), Matcher::Set(Box::new(set)), None)
+  }
+
+  /// Add a skip rule that matches `literal` exactly.
+  pub fn with_skip_literal(self, literal: &str) -> Self {
+    let pattern = regex_syntax::escape(literal);
+    self.with_matcher(None, Matcher::Literal(literal.chars().collect()), Some(pattern))
+  }
+
+  /// Add a skip rule that matches the regular expression `pattern`.
+  pub fn with_skip_regex(self, pattern: &str) -> Result<Self, regex::Error> {
+    let matcher = Matcher::Regex(Box::new(CharRegex::new(pattern)?));
+    Ok(self.with_matcher(None, matcher, Some(pattern.to_string())))
+  }
+
+  /// Add a skip rule that matches one or more elements of `set`.
+  pub fn with_skip_set<S>(self, set: S) -> Self
+  where
+    S: Set<char> + 'static, {
+    self.with_matcher(None, Matcher::Set(Box::new(set)), None)
+  }
+
+  /// Returns the length and the rule of the best match at the start of `input`.
+  fn longest_match(&self, input: &[char]) -> Option<(usize, &TokenRule<K>)> {
+    // The bound of a rule is the longest match it can have; set rules and a DFA that gives up bound nothing.
+    let mut bounds = self
+      .rules
+      .iter()
+      .map(|rule| if rule.pattern.is_some() { 0 } else { usize::MAX })
+      .collect::<Vec<_>>();
+    let automaton = self.automaton.get_or_init(|| RuleAutomaton::new(&self.rules));
+    if automaton
+      .as_ref()
+      .and_then(|automaton| automaton.bound_matches(input, &mut bounds))
+      .is_none()
+    {
+      bounds.fill(usize::MAX);
+    }
+    let mut best: Option<(usize, &TokenRule<K>)> = None;
+    for (rule, bound) in self.rules.iter().zip(bounds) {
+      let within_reach = match best {
+        _ if bound == 0 => false,
+        None => true,
+        Some((best_length, best_rule)) => (bound, rule.priority) > (best_length, best_rule.priority),
+      };
+      if !within_reach {
+        continue;
+      }
+      let length = rule.matcher.find_len(input);
+      let better = match best {
+        _ if length == 0 => false,
+        None => true,
+        Some((best_length, best_rule)) => (length, rule.priority) > (best_length, best_rule.priority),
+      };
+      if better {
+        best = Some((length, rule));
+      }
+    }
+    best
+  }
+
+  /// Split `input` into tokens, dropping the matches of skip rules.
+  ///
+  /// Spans are offsets of elements in `input`. Fails with [ParseError::Mismatch] at the first position no rule
+  /// matches.
+  pub fn tokenize<'a>(&self, input: &'a [char]) -> Result<Vec<Token<K>>, ParseError<'a, char>>
+  where
+    K: Clone, {
+    let mut tokens = vec![];
+    let mut offset = 0;
+    while offset < input.len() {
+      let (length, rule) = match self.longest_match(&input[offset..]) {
+        Some(matched) => matched,
+        None => {
+          let msg = format!("no token matches, found: {:?}", input[offset]);
+          return Err(ParseError::of_mismatch(input, offset, 1, msg));
+        }
+      };
+      if let Some(kind) = &rule.kind {
+        tokens.push(Token {
+          kind: kind.clone(),
+          text: input[offset..offset + length].iter().collect(),
+          span: offset..offset + length,
+        });
+      }
+      offset += length;
+    }
+    Ok(tokens)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+  use crate::util::CharSet;
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  enum Kind {
+    Lt,
+    Le,
+    Shl,
+    Word,
+  }
+
+  #[test]
+  fn test_tokenize_longest_match_and_priority() {
+    let spec = TokenizerSpec::new()
+      .with_skip_literal(" ")
+      .with_literal(Kind::Lt, "<")
+      .with_literal(Kind::Shl, "<<")
+      .with_literal(Kind::Le, "<=")
+      .with_set(Kind::Word, CharSet::from_class("a-z").unwrap())
+      .with_priority(1)
+      .with_regex(Kind::Lt, "lt")
+      .unwrap();
+    let input = "<<< <= lt ltx".chars().collect::<Vec<_>>();
+
+    let tokens = spec.tokenize(&input).unwrap();
+
+    let kinds = tokens.iter().map(|t| t.kind).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![Kind::Shl, Kind::Lt, Kind::Le, Kind::Lt, Kind::Word]);
+    assert_eq!(tokens[4].span, 10..13);
+  }
+
+  #[test]
+  fn test_tokenize_keeps_leftmost_first_regex_matches() {
+    let spec = TokenizerSpec::new()
+      .with_skip_set(CharSet::from_chars(" "))
+      .with_skip_regex(r"/\*.*?\*/")
+      .unwrap()
+      .with_regex(Kind::Word, r"\w+\b")
+      .unwrap();
+    let input = "/* a */ é x /* b */".chars().collect::<Vec<_>>();
+
+    let tokens = spec.tokenize(&input).unwrap();
+
+    let texts = tokens.iter().map(|t| t.text.as_str()).collect::<Vec<_>>();
+    assert_eq!(texts, vec!["é", "x"]);
+  }
+
+  #[test]
+  fn test_tokenize_reports_unmatched_offset() {
+    let spec = TokenizerSpec::new()
+      .with_skip_set(CharSet::from_chars(" "))
+      .with_set(Kind::Word, CharSet::from_class("a-z").unwrap());
+    let input = "ab c?d".chars().collect::<Vec<_>>();
+
+    let error = spec.tokenize(&input).unwrap_err();
+
+    assert!(error.is_mismatch());
+    assert_eq!(error.input().unwrap(), &['?']);
+  }
+}
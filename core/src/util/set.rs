@@ -1,4 +1,6 @@
-use std::cmp::{PartialEq, PartialOrd};
+use std::cell::OnceCell;
+use std::cmp::{Ordering, PartialEq, PartialOrd};
+use std::fmt::{Display, Formatter, Write};
 use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
 use std::str;
 
@@ -11,6 +13,103 @@ pub trait Set<T> {
   fn to_str(&self) -> &str {
     "<set>"
   }
+
+  /// Returns a set containing the elements of either `self` or `other`.
+  fn union<S>(self, other: S) -> Union<Self, S>
+  where
+    Self: Sized,
+    S: Set<T>, {
+    let description = format!("({} | {})", self.to_str(), other.to_str());
+    Union {
+      left: self,
+      right: other,
+      description,
+    }
+  }
+
+  /// Returns a set containing the elements of both `self` and `other`.
+  fn intersect<S>(self, other: S) -> Intersect<Self, S>
+  where
+    Self: Sized,
+    S: Set<T>, {
+    let description = format!("({} & {})", self.to_str(), other.to_str());
+    Intersect {
+      left: self,
+      right: other,
+      description,
+    }
+  }
+
+  /// Returns a set containing the elements not in `self`.
+  fn not(self) -> Not<Self>
+  where
+    Self: Sized, {
+    let description = format!("!{}", self.to_str());
+    Not { set: self, description }
+  }
+}
+
+impl<T, S: Set<T> + ?Sized> Set<T> for &S {
+  fn contains(&self, elem: &T) -> bool {
+    (**self).contains(elem)
+  }
+
+  fn to_str(&self) -> &str {
+    (**self).to_str()
+  }
+}
+
+/// The union of two sets. See [Set::union].
+#[derive(Debug, Clone)]
+pub struct Union<A, B> {
+  left: A,
+  right: B,
+  description: String,
+}
+
+impl<T, A: Set<T>, B: Set<T>> Set<T> for Union<A, B> {
+  fn contains(&self, elem: &T) -> bool {
+    self.left.contains(elem) || self.right.contains(elem)
+  }
+
+  fn to_str(&self) -> &str {
+    &self.description
+  }
+}
+
+/// The intersection of two sets. See [Set::intersect].
+#[derive(Debug, Clone)]
+pub struct Intersect<A, B> {
+  left: A,
+  right: B,
+  description: String,
+}
+
+impl<T, A: Set<T>, B: Set<T>> Set<T> for Intersect<A, B> {
+  fn contains(&self, elem: &T) -> bool {
+    self.left.contains(elem) && self.right.contains(elem)
+  }
+
+  fn to_str(&self) -> &str {
+    &self.description
+  }
+}
+
+/// The complement of a set. See [Set::not].
+#[derive(Debug, Clone)]
+pub struct Not<S> {
+  set: S,
+  description: String,
+}
+
+impl<T, S: Set<T>> Set<T> for Not<S> {
+  fn contains(&self, elem: &T) -> bool {
+    !self.set.contains(elem)
+  }
+
+  fn to_str(&self) -> &str {
+    &self.description
+  }
 }
 
 impl<T: PartialEq> Set<T> for [T] {
@@ -118,3 +217,434 @@ impl_set_for_char_array!(
   32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60,
   61, 62, 63, 64
 );
+
+/// An error in the syntax of a character class such as `[A-Za-z0-9_]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassSyntaxError {
+  offset: usize,
+  message: String,
+}
+
+impl ClassSyntaxError {
+  fn new(offset: usize, message: &str) -> Self {
+    Self {
+      offset,
+      message: message.to_string(),
+    }
+  }
+
+  /// Returns the offset of the error in the class, in characters.
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+}
+
+impl Display for ClassSyntaxError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} at {}", self.message, self.offset)
+  }
+}
+
+impl std::error::Error for ClassSyntaxError {}
+
+/// Parses a character class into a negation flag and inclusive ranges.
+///
+/// The class may be surrounded by `[` and `]`, in which case a leading `^` negates it. `-` between two characters
+/// denotes a range, and `\\`, `\n`, `\r`, `\t`, `\xNN` and `\u{N}` are escapes.
+fn parse_class(class: &str) -> Result<(bool, Vec<(char, char)>), ClassSyntaxError> {
+  let chars = class.chars().collect::<Vec<_>>();
+  let (mut index, end) = match chars.first() {
+    Some('[') if chars.len() >= 2 && chars[chars.len() - 1] == ']' => (1, chars.len() - 1),
+    Some('[') => return Err(ClassSyntaxError::new(chars.len(), "missing ]")),
+    _ => (0, chars.len()),
+  };
+  let negated = index == 1 && chars.get(1) == Some(&'^') && end > 1;
+  if negated {
+    index += 1;
+  }
+  let mut ranges = vec![];
+  while index < end {
+    let (start, next) = parse_class_char(&chars, index, end)?;
+    if next + 1 < end && chars[next] == '-' {
+      let (last, next_range) = parse_class_char(&chars, next + 1, end)?;
+      if last < start {
+        return Err(ClassSyntaxError::new(index, "range out of order"));
+      }
+      ranges.push((start, last));
+      index = next_range;
+    } else {
+      ranges.push((start, start));
+      index = next;
+    }
+  }
+  Ok((negated, ranges))
+}
+
+pub(crate) fn parse_class_char(chars: &[char], index: usize, end: usize) -> Result<(char, usize), ClassSyntaxError> {
+  if chars[index] != '\\' {
+    return Ok((chars[index], index + 1));
+  }
+  let escaped = match chars.get(index + 1) {
+    Some(c) if index + 1 < end => *c,
+    _ => return Err(ClassSyntaxError::new(index, "incomplete escape")),
+  };
+  let hex = |digits: &[char]| {
+    u32::from_str_radix(&String::from_iter(digits), 16)
+      .ok()
+      .and_then(char::from_u32)
+      .ok_or_else(|| ClassSyntaxError::new(index, "invalid code point"))
+  };
+  match escaped {
+    'n' => Ok(('\n', index + 2)),
+    'r' => Ok(('\r', index + 2)),
+    't' => Ok(('\t', index + 2)),
+    'x' if index + 4 <= end => Ok((hex(&chars[index + 2..index + 4])?, index + 4)),
+    'x' => Err(ClassSyntaxError::new(index, "incomplete escape")),
+    'u' => match chars[..end].iter().skip(index + 3).position(|c| *c == '}') {
+      Some(length) if chars[index + 2] == '{' => Ok((hex(&chars[index + 3..index + 3 + length])?, index + 4 + length)),
+      _ => Err(ClassSyntaxError::new(index, "incomplete escape")),
+    },
+    c => Ok((c, index + 2)),
+  }
+}
+
+fn write_class_char(description: &mut String, c: char, bytes: bool) {
+  match c {
+    '\\' | ']' | '[' | '^' | '-' => write!(description, "\\{}", c).unwrap(),
+    '\n' => description.push_str("\\n"),
+    '\r' => description.push_str("\\r"),
+    '\t' => description.push_str("\\t"),
+    c if (bytes && !c.is_ascii()) || (c.is_control() && (c as u32) < 0x100) => {
+      write!(description, "\\x{:02X}", c as u32).unwrap()
+    }
+    c if c.is_control() => write!(description, "\\u{{{:X}}}", c as u32).unwrap(),
+    c => description.push(c),
+  }
+}
+
+fn class_description(ranges: impl Iterator<Item = (char, char)>, bytes: bool) -> String {
+  let mut description = String::from("[");
+  for (start, end) in ranges {
+    write_class_char(&mut description, start, bytes);
+    if start != end {
+      if end as u32 - start as u32 > 1 {
+        description.push('-');
+      }
+      write_class_char(&mut description, end, bytes);
+    }
+  }
+  description.push(']');
+  description
+}
+
+/// A set of bytes backed by a 256-bit bitset.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// use twill_core::util::{ByteSet, Set};
+///
+/// let text: &str = "a_1-";
+/// let input = text.as_bytes();
+///
+/// let word = ByteSet::from_class("[A-Za-z0-9_]").unwrap();
+/// let parser = elm_of(&word).of_many1();
+///
+/// let result = parser.parse(input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.success().unwrap(), b"a_1");
+/// assert_eq!(word.to_str(), "[0-9A-Z_a-z]");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ByteSet {
+  bits: [u64; 4],
+  description: OnceCell<String>,
+}
+
+impl ByteSet {
+  /// Create an empty set.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Create a set of the given bytes.
+  pub fn from_bytes(bytes: &[u8]) -> Self {
+    Self::new().with_bytes(bytes)
+  }
+
+  /// Create a set from a class such as `[A-Za-z0-9_]` or `[^\x00-\x1F]`.
+  ///
+  /// Returns an error if the class is malformed or contains a character above `\xFF`.
+  pub fn from_class(class: &str) -> Result<Self, ClassSyntaxError> {
+    let (negated, ranges) = parse_class(class)?;
+    let mut set = Self::new();
+    for (start, end) in ranges {
+      let (start, end) = match (u8::try_from(start), u8::try_from(end)) {
+        (Ok(start), Ok(end)) => (start, end),
+        _ => return Err(ClassSyntaxError::new(0, "non-byte character in byte class")),
+      };
+      set = set.with_range(start, end);
+    }
+    Ok(if negated { set.complement() } else { set })
+  }
+
+  /// Add a byte.
+  pub fn with_byte(mut self, byte: u8) -> Self {
+    self.insert(byte);
+    self
+  }
+
+  /// Add bytes.
+  pub fn with_bytes(mut self, bytes: &[u8]) -> Self {
+    bytes.iter().for_each(|byte| self.insert(*byte));
+    self
+  }
+
+  /// Add the bytes from `start` to `end`, inclusive.
+  pub fn with_range(mut self, start: u8, end: u8) -> Self {
+    (start..=end).for_each(|byte| self.insert(byte));
+    self
+  }
+
+  /// Add a byte.
+  pub fn insert(&mut self, byte: u8) {
+    self.bits[usize::from(byte >> 6)] |= 1 << (byte & 63);
+    self.description = OnceCell::new();
+  }
+
+  /// Returns a set containing the bytes not in `self`.
+  pub fn complement(&self) -> Self {
+    Self {
+      bits: self.bits.map(|bits| !bits),
+      description: OnceCell::new(),
+    }
+  }
+
+  fn has(&self, byte: u8) -> bool {
+    self.bits[usize::from(byte >> 6)] & (1 << (byte & 63)) != 0
+  }
+}
+
+impl PartialEq for ByteSet {
+  fn eq(&self, other: &Self) -> bool {
+    self.bits == other.bits
+  }
+}
+
+impl Eq for ByteSet {}
+
+impl Set<u8> for ByteSet {
+  fn contains(&self, elem: &u8) -> bool {
+    self.has(*elem)
+  }
+
+  fn to_str(&self) -> &str {
+    self.description.get_or_init(|| {
+      let mut ranges: Vec<(char, char)> = vec![];
+      for byte in (0..=255u8).filter(|byte| self.has(*byte)) {
+        let c = char::from(byte);
+        match ranges.last_mut() {
+          Some((_, end)) if *end as u32 + 1 == c as u32 => *end = c,
+          _ => ranges.push((c, c)),
+        }
+      }
+      class_description(ranges.into_iter(), true)
+    })
+  }
+}
+
+/// A set of characters backed by a sorted list of ranges.
+///
+/// Lookups are a binary search over the ranges, so large classes stay cheap.
+///
+/// # Example
+///
+/// ```rust
+/// # use twill_core::prelude::*;
+/// use twill_core::util::{CharSet, Set};
+///
+/// let text: &str = "héllo wörld";
+/// let input = text.chars().collect::<Vec<_>>();
+///
+/// let letters = CharSet::from_class("[a-z]").unwrap().union(CharSet::from_chars("éö"));
+/// let parser = elm_of(&letters).of_many1();
+///
+/// let result = parser.parse(&input);
+///
+/// assert!(result.is_success());
+/// assert_eq!(result.consumed_count(), 5);
+/// assert_eq!(letters.to_str(), "([a-z] | [éö])");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharSet {
+  ranges: Vec<(char, char)>,
+  description: String,
+}
+
+impl Default for CharSet {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl CharSet {
+  /// Create an empty set.
+  pub fn new() -> Self {
+    Self {
+      ranges: vec![],
+      description: class_description(std::iter::empty(), false),
+    }
+  }
+
+  /// Create a set of the characters in `chars`.
+  pub fn from_chars(chars: &str) -> Self {
+    Self::from_ranges(chars.chars().map(|c| (c, c)))
+  }
+
+  /// Create a set from a class such as `[A-Za-z0-9_]` or `[^"\\]`.
+  pub fn from_class(class: &str) -> Result<Self, ClassSyntaxError> {
+    let (negated, ranges) = parse_class(class)?;
+    let set = Self::from_ranges(ranges);
+    Ok(if negated { set.complement() } else { set })
+  }
+
+  /// Create a set from inclusive ranges.
+  pub fn from_ranges(ranges: impl IntoIterator<Item = (char, char)>) -> Self {
+    let mut ranges = ranges.into_iter().collect::<Vec<_>>();
+    ranges.sort();
+    let mut merged: Vec<(char, char)> = vec![];
+    for (start, end) in ranges {
+      match merged.last_mut() {
+        Some((_, last)) if next_char(*last).is_none_or(|next| start <= next) => *last = (*last).max(end),
+        _ => merged.push((start, end)),
+      }
+    }
+    let description = class_description(merged.iter().copied(), false);
+    Self {
+      ranges: merged,
+      description,
+    }
+  }
+
+  /// Add a character.
+  pub fn with_char(self, c: char) -> Self {
+    self.with_range(c, c)
+  }
+
+  /// Add the characters in `chars`.
+  pub fn with_chars(self, chars: &str) -> Self {
+    Self::from_ranges(self.ranges.into_iter().chain(chars.chars().map(|c| (c, c))))
+  }
+
+  /// Add the characters from `start` to `end`, inclusive.
+  pub fn with_range(self, start: char, end: char) -> Self {
+    Self::from_ranges(self.ranges.into_iter().chain(std::iter::once((start, end))))
+  }
+
+  /// Returns a set containing the characters not in `self`.
+  pub fn complement(&self) -> Self {
+    let mut ranges = vec![];
+    let mut start = Some('\0');
+    for (first, last) in &self.ranges {
+      if let Some(start) = start.filter(|start| start < first) {
+        ranges.push((start, prev_char(*first).unwrap()));
+      }
+      start = next_char(*last);
+    }
+    if let Some(start) = start {
+      ranges.push((start, char::MAX));
+    }
+    Self::from_ranges(ranges)
+  }
+}
+
+fn next_char(c: char) -> Option<char> {
+  match c {
+    '\u{D7FF}' => Some('\u{E000}'),
+    c => char::from_u32(c as u32 + 1),
+  }
+}
+
+fn prev_char(c: char) -> Option<char> {
+  match c {
+    '\u{E000}' => Some('\u{D7FF}'),
+    c => char::from_u32((c as u32).checked_sub(1)?),
+  }
+}
+
+impl Set<char> for CharSet {
+  fn contains(&self, elem: &char) -> bool {
+    self
+      .ranges
+      .binary_search_by(|(start, end)| {
+        if end < elem {
+          Ordering::Less
+        } else if start > elem {
+          Ordering::Greater
+        } else {
+          Ordering::Equal
+        }
+      })
+      .is_ok()
+  }
+
+  fn to_str(&self) -> &str {
+    &self.description
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::prelude::*;
+  use crate::util::{ByteSet, CharSet, Set};
+
+  #[test]
+  fn test_set_algebra() {
+    let set = ('a'..='z')
+      .union("0123")
+      .intersect(CharSet::from_class("[^x-z3]").unwrap())
+      .not();
+
+    assert!(!set.contains(&'a'));
+    assert!(set.contains(&'x'));
+    assert!(set.contains(&'3'));
+    assert!(set.contains(&'A'));
+  }
+
+  #[test]
+  fn test_none_of_ref_message() {
+    let text = "\"";
+    let input = text.chars().collect::<Vec<_>>();
+    let set = CharSet::from_class(r#"["\\]"#).unwrap();
+
+    let result = none_of_ref(&set).parse(&input);
+
+    let message = match result.failure().unwrap() {
+      ParseError::Mismatch { message, .. } => message,
+      error => panic!("unexpected error: {}", error),
+    };
+    assert_eq!(message, r#"expect none of: ["\\], found: ""#);
+  }
+
+  #[test]
+  fn test_char_set_complement_skips_surrogates() {
+    let set = CharSet::from_class("[\\x00-\\u{D7FF}\\u{E001}-\\u{10FFFF}]").unwrap();
+
+    assert_eq!(set.complement(), CharSet::from_chars("\u{E000}"));
+    assert_eq!(set.complement().complement(), set);
+  }
+
+  #[test]
+  fn test_byte_set_class() {
+    let set = ByteSet::from_class("[^\\x00-\\x1F\\x7F-\\xFF]").unwrap();
+
+    assert!(set.contains(&b' '));
+    assert!(!set.contains(&b'\n'));
+    assert_eq!(set.to_str(), "[ -~]");
+    assert!(ByteSet::from_class("[a-é]").is_ok());
+    assert!(ByteSet::from_class("[a-ā]").is_err());
+    assert!(CharSet::from_class("[z-a]").is_err());
+  }
+}
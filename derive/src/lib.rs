@@ -0,0 +1,218 @@
+//! Derive macros for [twill-core](https://docs.rs/twill-core).
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+  parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument, LitStr, Path, PathArguments, Type,
+};
+
+/// Derives `twill_core::prelude::Parse`, generating `fn parser<'a>() -> impl ParserRunner<'a, char, Self>`.
+///
+/// Fields are parsed in declaration order with the `Parse` implementation of their type, and enum variants are tried
+/// in declaration order. `Vec<T>`, `Option<T>` and `Box<T>` fields are parsed from `T`. Every token skips trailing
+/// whitespace.
+///
+/// Attributes, on the type, a variant or a field:
+///
+/// - `#[twill(tag = "let")]`: a token before the item. Identifier-like tags are keywords, which must not be followed by
+///   an identifier character.
+/// - `#[twill(surround("(", ")"))]`: tokens around the item.
+///
+/// And on a field only:
+///
+/// - `#[twill(sep = ",")]`: the separator of the items of a `Vec<T>` field.
+/// - `#[twill(with = path::to::parser)]`: a function returning the parser of the field, instead of `Parse`.
+#[proc_macro_derive(Parse, attributes(twill))]
+pub fn derive_parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct TwillAttrs {
+  tag: Option<LitStr>,
+  surround: Option<(LitStr, LitStr)>,
+  sep: Option<LitStr>,
+  with: Option<Path>,
+}
+
+impl TwillAttrs {
+  fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+    let mut result = Self::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("twill")) {
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("tag") {
+          result.tag = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("sep") {
+          result.sep = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("with") {
+          result.with = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("surround") {
+          let content;
+          syn::parenthesized!(content in meta.input);
+          let open = content.parse()?;
+          content.parse::<syn::Token![,]>()?;
+          result.surround = Some((open, content.parse()?));
+        } else {
+          return Err(meta.error("expected tag, sep, surround or with"));
+        }
+        Ok(())
+      })?;
+    }
+    Ok(result)
+  }
+
+  fn reject_field_only(&self, span: proc_macro2::Span) -> syn::Result<()> {
+    if self.sep.is_some() || self.with.is_some() {
+      return Err(syn::Error::new(span, "sep and with are only allowed on fields"));
+    }
+    Ok(())
+  }
+
+  /// Wraps `parser` with the tag and the surrounding tokens.
+  fn wrap(&self, parser: TokenStream) -> TokenStream {
+    let parser = match &self.tag {
+      Some(tag) => {
+        let tag = token(tag);
+        quote!(::twill_core::__boxed(#tag).skip_left(#parser))
+      }
+      None => parser,
+    };
+    match &self.surround {
+      Some((open, close)) => {
+        let (open, close) = (token(open), token(close));
+        quote!(::twill_core::prelude::surround(#open, #parser, #close))
+      }
+      None => parser,
+    }
+  }
+}
+
+/// Returns a keyword parser for identifier-like text, and a symbol parser otherwise.
+fn token(text: &LitStr) -> TokenStream {
+  if text.value().chars().all(|c| c.is_alphanumeric() || c == '_') {
+    quote!(lexer.keyword(#text))
+  } else {
+    quote!(lexer.symbol(#text))
+  }
+}
+
+/// Returns `T` if `ty` is `wrapper<T>`.
+fn inner_type<'t>(ty: &'t Type, wrapper: &str) -> Option<&'t Type> {
+  let Type::Path(path) = ty else { return None };
+  let segment = path.path.segments.last()?;
+  let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+    return None;
+  };
+  match arguments.args.first() {
+    Some(GenericArgument::Type(inner)) if segment.ident == wrapper && arguments.args.len() == 1 => Some(inner),
+    _ => None,
+  }
+}
+
+fn field_parser(ty: &Type, attrs: &TwillAttrs) -> syn::Result<TokenStream> {
+  let parse = |ty: &Type| quote!(<#ty as ::twill_core::prelude::Parse>::parser());
+  let parser = if let Some(with) = &attrs.with {
+    quote!(#with())
+  } else if let Some(inner) = inner_type(ty, "Vec") {
+    let item = parse(inner);
+    match &attrs.sep {
+      Some(sep) => {
+        let sep = token(sep);
+        quote!(#item.of_many0_sep(#sep))
+      }
+      None => quote!(#item.of_many0()),
+    }
+  } else if let Some(inner) = inner_type(ty, "Option") {
+    // The tokens of an optional field are optional too.
+    let item = field_parser(inner, attrs)?;
+    return Ok(quote!(::twill_core::__boxed(#item.opt())));
+  } else if let Some(inner) = inner_type(ty, "Box") {
+    let item = parse(inner);
+    quote!(#item.map(::std::boxed::Box::new))
+  } else {
+    parse(ty)
+  };
+  if attrs.sep.is_some() && inner_type(ty, "Vec").is_none() {
+    return Err(syn::Error::new(ty.span(), "sep is only allowed on Vec fields"));
+  }
+  Ok(attrs.wrap(quote!(::twill_core::__boxed(#parser))))
+}
+
+/// Returns a parser of `fields` that builds its value with `constructor`.
+fn fields_parser(fields: &Fields, constructor: TokenStream) -> syn::Result<TokenStream> {
+  let mut parsers = vec![];
+  let mut names = vec![];
+  for (index, field) in fields.iter().enumerate() {
+    parsers.push(field_parser(&field.ty, &TwillAttrs::from_attrs(&field.attrs)?)?);
+    names.push(field.ident.clone().unwrap_or_else(|| format_ident!("__field{}", index)));
+  }
+  let value = match fields {
+    Fields::Named(_) => quote!(#constructor { #(#names),* }),
+    Fields::Unnamed(_) => quote!(#constructor(#(#names),*)),
+    Fields::Unit => quote!(#constructor),
+  };
+  let mut iter = parsers.into_iter().zip(&names);
+  let Some((first, first_name)) = iter.next() else {
+    return Ok(quote!(::twill_core::prelude::unit().map(|_| #value)));
+  };
+  let (parser, pattern) = iter.fold((first, quote!(#first_name)), |(parser, pattern), (next, name)| {
+    (quote!(#parser.and_then(#next)), quote!((#pattern, #name)))
+  });
+  Ok(quote!(#parser.map(|#pattern| #value)))
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+  let attrs = TwillAttrs::from_attrs(&input.attrs)?;
+  attrs.reject_field_only(input.ident.span())?;
+  let body = match &input.data {
+    Data::Struct(data) => fields_parser(&data.fields, quote!(Self))?,
+    Data::Enum(data) => {
+      let mut variants = vec![];
+      for variant in &data.variants {
+        let variant_attrs = TwillAttrs::from_attrs(&variant.attrs)?;
+        variant_attrs.reject_field_only(variant.ident.span())?;
+        if matches!(variant.fields, Fields::Unit) && variant_attrs.tag.is_none() && variant_attrs.surround.is_none() {
+          return Err(syn::Error::new(variant.ident.span(), "unit variants need a tag"));
+        }
+        let ident = &variant.ident;
+        let parser = variant_attrs.wrap(fields_parser(&variant.fields, quote!(Self::#ident))?);
+        variants.push(quote!(::twill_core::__boxed(#parser).attempt()));
+      }
+      let Some(first) = variants.first() else {
+        return Err(syn::Error::new(
+          input.ident.span(),
+          "enums without variants cannot be parsed",
+        ));
+      };
+      let rest = &variants[1..];
+      quote!(#first #(.or(#rest))*)
+    }
+    Data::Union(_) => return Err(syn::Error::new(input.ident.span(), "unions cannot be parsed")),
+  };
+  let body = attrs.wrap(quote!(::twill_core::__boxed(#body)));
+
+  let name = &input.ident;
+  let mut generics = input.generics.clone();
+  for param in generics.type_params_mut() {
+    param.bounds.push(syn::parse_quote!(::twill_core::prelude::Parse));
+  }
+  let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics ::twill_core::prelude::Parse for #name #type_generics #where_clause {
+      fn parser<'a>() -> impl ::twill_core::prelude::ParserRunner<'a, char, Self> {
+        #[allow(unused_imports)]
+        use ::twill_core::prelude::{
+          AndThenParser as _, AttemptParser as _, OptParser as _, OrParser as _, ParserMonad as _, ParserRunner as _,
+          RepeatParser as _, SkipParser as _,
+        };
+        ::twill_core::prelude::Parser::new(|parse_context: ::twill_core::prelude::ParseContext<'a, char>| {
+          #[allow(unused_variables)]
+          let lexer = ::twill_core::prelude::Lexer::new();
+          #body.run(parse_context)
+        })
+      }
+    }
+  })
+}